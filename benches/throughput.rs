@@ -0,0 +1,76 @@
+//! Tokenization throughput on generated multi-MB sources.
+//!
+//! Run with `cargo bench --bench throughput`.
+
+use std::time::{Duration, Instant};
+
+use tfla::{MatchPolicy, TFLA};
+
+const SIZES: [usize; 2] = [1 << 20, 4 << 20];
+const RUNS: u32 = 3;
+
+fn searchers() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^[ \t]+"),
+        ("Fn", r"^fn\b"),
+        ("Let", r"^let\b"),
+        ("If", r"^if\b"),
+        ("Else", r"^else\b"),
+        ("Return", r"^return\b"),
+        ("While", r"^while\b"),
+        ("Number", r"^\d+(\.\d+)?"),
+        ("String", r#"^"([^"\\]|\\.)*""#),
+        ("Entity", r"^[A-Za-z_]\w*"),
+        ("Arrow", r"^->"),
+        ("GreaterEqual", r"^>="),
+        ("LessEqual", r"^<="),
+        ("Equal", r"^=="),
+        ("Operator", r"^[-+*/%<>=!&|]"),
+        ("Punctuation", r"^[(){}\[\];,.:]"),
+    ]
+}
+
+fn source(size: usize) -> String {
+    let snippet = r#"fn fibonacci(n: u64) -> u64 {
+    let a = 0; let b = 1.5;
+    while n >= 2 { if a <= b { return a + b * 3; } else { a = "it's \"quoted\""; } }
+}
+"#;
+
+    snippet.repeat(size / snippet.len() + 1)
+}
+
+fn bench(name: &str, code: &str, policy: MatchPolicy) {
+    let mut best = Duration::MAX;
+    let mut count = 0;
+
+    for _ in 0..RUNS {
+        let mut tfla = TFLA::new(searchers(), vec![], true);
+        tfla.match_policy = policy;
+
+        let start = Instant::now();
+        count = tfla.tokenize(code).unwrap().len();
+        best = best.min(start.elapsed());
+    }
+
+    let mb = code.len() as f64 / (1 << 20) as f64;
+    println!(
+        "{:<24} {:>6.1} MB {:>10} tokens {:>9.2?} {:>8.2} MB/s",
+        name,
+        mb,
+        count,
+        best,
+        mb / best.as_secs_f64()
+    );
+}
+
+fn main() {
+    for size in SIZES {
+        let code = source(size);
+
+        bench("first match", &code, MatchPolicy::First);
+        bench("longest match", &code, MatchPolicy::Longest);
+        bench("priority match", &code, MatchPolicy::Priority);
+    }
+}
@@ -0,0 +1,676 @@
+use regex::Regex;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::precedence::{Assoc, Precedence};
+use crate::Token;
+
+/// When less than this stack is left, the recursive functions continue in a
+/// new stack of `STACK_SIZE`, so deep nested rules don't overflow.
+const RED_ZONE: usize = 64 * 1024;
+const STACK_SIZE: usize = 1024 * 1024;
+
+/// The symbols predefined by TFLA CC, available to every assembler.
+const PREDEFINED_SYMBOLS: [(&str, &str); 6] = [
+    ("nwl", r"(\r)?\n"),
+    ("eof", r"\z"),
+    ("eol", r"$"),
+    ("tab", r"\t"),
+    ("noh", r"\x00"),
+    ("num", r"\d+"),
+];
+
+/// One argument of an assembler alternative, decoded from the
+/// `as-`/`se-`/`sy-`/`li-` prefixes written by TFLA CC.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Argument<'a> {
+    Assembler(&'a str),
+    Searcher(&'a str),
+    Symbol(&'a str),
+    Literal(&'a str),
+    Empty,
+}
+
+impl<'a> Argument<'a> {
+    pub(crate) fn from(arbitrary: &'a str) -> Self {
+        let (prefix, value) = match arbitrary.find('-') {
+            Some(i) => (&arbitrary[..i], &arbitrary[i + 1..]),
+            None => ("li", arbitrary),
+        };
+
+        match prefix {
+            "as" => Argument::Assembler(value),
+            "se" => Argument::Searcher(value),
+            "sy" => Argument::Symbol(value),
+            "li" if value == "ε" => Argument::Empty,
+            "li" => Argument::Literal(unquote(value)),
+            _ => Argument::Literal(unquote(arbitrary)),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Argument<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Argument::Assembler(name) => write!(f, "<{}>", name),
+            Argument::Searcher(name) => write!(f, "[{}]", name),
+            Argument::Symbol(name) => write!(f, ":{}:", name),
+            Argument::Literal(value) => write!(f, "{:?}", value),
+            Argument::Empty => write!(f, "ε"),
+        }
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+/// A child of a [`Node`], either a nested rule or a matched token.
+#[derive(Debug, Clone)]
+pub enum Child<'a> {
+    Node(Node<'a>),
+    Token(Token<'a>),
+}
+
+/// A node of the AST, built when an assembler matches a sequence of tokens.
+///
+/// `start` and `end` are indices into the token list that was assembled,
+/// `end` being exclusive.
+#[derive(Debug, Clone)]
+pub struct Node<'a> {
+    pub rule: &'a str,
+    pub children: Vec<Child<'a>>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl<'a> Node<'a> {
+    /// The children of the assemblers with a `#` in the name, like
+    /// `list#3.1`, are moved to this node. They're made by tfla-cc for the
+    /// groups, like `(args)","+`, so the groups don't make new nodes.
+    pub fn new(rule: &'a str, children: Vec<Child<'a>>, start: usize, end: usize) -> Self {
+        let mut flat = Vec::with_capacity(children.len());
+
+        for child in children {
+            match child {
+                Child::Node(mut node) if node.rule.contains('#') => flat.append(&mut node.children),
+                child => flat.push(child),
+            }
+        }
+
+        Node {
+            rule,
+            children: flat,
+            start,
+            end,
+        }
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        stacker::maybe_grow(RED_ZONE, STACK_SIZE, || self.write_node(f, depth))
+    }
+
+    fn write_node(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{}<{}> {}..{}",
+            "  ".repeat(depth),
+            self.rule,
+            self.start,
+            self.end
+        )?;
+
+        for child in &self.children {
+            match child {
+                Child::Node(node) => node.write_tree(f, depth + 1)?,
+                Child::Token(token) => writeln!(
+                    f,
+                    "{}[{}] {:?} |{} row {}|",
+                    "  ".repeat(depth + 1),
+                    token.ty,
+                    token.content,
+                    token.line,
+                    token.start
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Node<'_> {
+    /// The children are dropped without recursion, a right recursive
+    /// assembler makes a node as deep as the tokens.
+    fn drop(&mut self) {
+        let mut children = std::mem::take(&mut self.children);
+
+        while let Some(child) = children.pop() {
+            if let Child::Node(mut node) = child {
+                children.append(&mut node.children);
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Display for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AstError {
+    /// The requested rule is not defined by any assembler.
+    UnknownRule(String),
+    /// `TFLA::assemble_lr` was called without an LR table, or with a table
+    /// that don't match the assemblers.
+    NoLrTable,
+    /// The tokens don't match the rule, `index` is the furthest token where
+    /// an argument failed, with what was found there and what the arguments
+    /// that failed there expected.
+    Unexpected {
+        index: usize,
+        found: Option<String>,
+        expected: Vec<String>,
+    },
+}
+
+impl fmt::Display for AstError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AstError::UnknownRule(rule) => write!(f, "The Assembler <{}> don't exists.", rule),
+            AstError::NoLrTable => write!(
+                f,
+                "The TFLA don't have a valid LR table, compile the TFLAC with tfla-cc -c."
+            ),
+            AstError::Unexpected {
+                index,
+                found,
+                expected,
+            } => {
+                match found {
+                    Some(content) => write!(f, "Unexpected {:?} at token {}", content, index)?,
+                    None => write!(f, "Unexpected end of tokens at token {}", index)?,
+                }
+
+                write!(f, ", expected {}.", expected.join(" or "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for AstError {}
+
+/// The TFLA ASTGen, it assembles the tokens found by the TFLA Tokenizer into
+/// an AST following the configured assemblers.
+///
+/// It's a PEG parser: alternatives of the same assembler are an ordered
+/// choice, tried in the declared order, and the first one that matches is
+/// used without going back to the others. The result of each assembler at
+/// each token is memoized (packrat parsing), so no assembler is tried twice
+/// at the same token and the time grows linearly with the tokens. The memo
+/// keeps the nodes by index, the AST is only made once at the end.
+pub struct ASTGen<'a> {
+    pub(crate) assemblers: Vec<(&'a str, Vec<Argument<'a>>)>,
+    symbols: Vec<(&'a str, Regex)>,
+    /// The operators with a precedence, with their level, from 1, and their
+    /// associativity.
+    operators: Vec<(Argument<'a>, usize, Assoc)>,
+    /// The assemblers expanded by the last `generate`, see `expanded`.
+    expanded: Cell<usize>,
+}
+
+impl<'a> ASTGen<'a> {
+    pub fn new(assemblers: Vec<(&'a str, Vec<&'a str>)>, symbols: Vec<(&'a str, &'a str)>) -> Self {
+        let mut a: Vec<(&'a str, Vec<Argument<'a>>)> = vec![];
+
+        for (name, arbitrary) in &assemblers {
+            a.push((name, arbitrary.iter().map(|b| Argument::from(b)).collect()));
+        }
+
+        let mut s: Vec<(&'a str, Regex)> = vec![];
+
+        for (name, regex) in PREDEFINED_SYMBOLS.iter().chain(&symbols) {
+            let r = Regex::new(&format!(r"^(?:{})\z", regex)).unwrap();
+            s.push((name, r));
+        }
+
+        ASTGen {
+            assemblers: a,
+            symbols: s,
+            operators: vec![],
+            expanded: Cell::new(0),
+        }
+    }
+
+    /// How many times the last `generate` expanded an assembler, at most once
+    /// for each assembler at each token, since the results are memoized.
+    pub fn expanded(&self) -> usize {
+        self.expanded.get()
+    }
+
+    /// Adds a precedence level tighter than the ones already added.
+    ///
+    /// An assembler with options like `<e> "+" <e>`, where all the operators
+    /// have a precedence, is assembled with a Pratt parser: the operators
+    /// tighter than the others are assembled first. The options like
+    /// `"-" <e>` are prefix operators, tighter than all the binary ones,
+    /// unless they have a precedence, then they take the operators tighter
+    /// than them, like the tfla-cc LR table does. The other options are the
+    /// operands, tried in the declared order.
+    pub fn add_precedence(&mut self, precedence: &Precedence<'a>) {
+        let level = self.operators.last().map_or(1, |(_, level, _)| level + 1);
+
+        for operator in &precedence.operators {
+            self.operators
+                .push((Argument::from(operator), level, precedence.assoc));
+        }
+    }
+
+    fn precedence(&self, operator: &Argument<'a>) -> Option<(usize, Assoc)> {
+        self.operators
+            .iter()
+            .find(|(o, ..)| o == operator)
+            .map(|(_, level, assoc)| (*level, *assoc))
+    }
+
+    /// The binary and prefix operators of the assembler, if it has binary
+    /// operators and all of them have a precedence.
+    fn operators(&self, rule: &'a str) -> Option<Operators<'a, '_>> {
+        let mut operators = Operators {
+            binary: vec![],
+            prefix: vec![],
+            operands: vec![],
+        };
+        let max = self.operators.last().map_or(0, |(_, level, _)| *level);
+
+        for (name, arguments) in &self.assemblers {
+            if *name != rule {
+                continue;
+            }
+
+            match arguments[..] {
+                [Argument::Assembler(l), operator, Argument::Assembler(r)]
+                    if l == rule && r == rule =>
+                {
+                    let (level, assoc) = self.precedence(&operator)?;
+                    operators.binary.push((operator, level, assoc));
+                }
+                [operator, Argument::Assembler(r)]
+                    if r == rule && !matches!(operator, Argument::Assembler(_)) =>
+                {
+                    let next = match self.precedence(&operator) {
+                        Some((level, Assoc::Right)) => level,
+                        Some((level, _)) => level + 1,
+                        None => max + 1,
+                    };
+                    operators.prefix.push((operator, next));
+                }
+                _ => operators.operands.push(arguments),
+            }
+        }
+
+        (!operators.binary.is_empty()).then_some(operators)
+    }
+
+    pub fn generate(&self, tokens: &[Token<'a>], root: &'a str) -> Result<Node<'a>, AstError> {
+        if !self.assemblers.iter().any(|(name, _)| *name == root) {
+            return Err(AstError::UnknownRule(root.to_string()));
+        }
+
+        let mut packrat = Packrat::new(self, tokens);
+        let res = packrat.assemble(root, 0);
+        self.expanded.set(packrat.expanded);
+
+        match res {
+            Some(node) if packrat.end(node) == tokens.len() => return Ok(packrat.node(node)),
+            Some(node) => packrat.fail(packrat.end(node), "end of tokens".to_string()),
+            None => (),
+        }
+
+        Err(AstError::Unexpected {
+            index: packrat.furthest,
+            found: tokens.get(packrat.furthest).map(|t| t.content.to_string()),
+            expected: packrat.expected,
+        })
+    }
+
+    pub(crate) fn token_match(&self, argument: &Argument<'a>, token: &Token<'a>) -> bool {
+        match *argument {
+            Argument::Searcher(name) => token.ty == name,
+            Argument::Symbol(name) => self.symbol_match(name, token.content),
+            Argument::Literal(value) => token.content == value,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn symbol_match(&self, name: &str, content: &str) -> bool {
+        // User symbols are checked first so they can redefine a predefined one.
+        self.symbols
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .is_some_and(|(_, regex)| regex.is_match(content))
+    }
+}
+
+/// The options of an assembler assembled with a Pratt parser, see
+/// `ASTGen::add_precedence`.
+struct Operators<'a, 'g> {
+    binary: Vec<(Argument<'a>, usize, Assoc)>,
+    /// The prefix operators, with the lowest level that their operand takes.
+    prefix: Vec<(Argument<'a>, usize)>,
+    operands: Vec<&'g [Argument<'a>]>,
+}
+
+/// A node assembled by [`Packrat`], with its children as indexes of the
+/// nodes and tokens, so the memo don't copy the nodes.
+struct Assembled<'a> {
+    rule: &'a str,
+    children: Vec<Part>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Part {
+    Node(usize),
+    Token(usize),
+}
+
+/// The state of one [`ASTGen::generate`].
+struct Packrat<'g, 'a> {
+    ast_gen: &'g ASTGen<'a>,
+    tokens: &'g [Token<'a>],
+    nodes: Vec<Assembled<'a>>,
+    memo: HashMap<(&'a str, usize), Option<usize>>,
+    /// The assemblers being expanded, with the token where they started, and
+    /// how many were being expanded before them.
+    active: HashMap<(&'a str, usize), usize>,
+    /// The lowest depth in `active` of an assembler that was abandoned for
+    /// expanding itself, since the last one was expanded.
+    cut: usize,
+    expanded: usize,
+    furthest: usize,
+    expected: Vec<String>,
+}
+
+impl<'g, 'a> Packrat<'g, 'a> {
+    fn new(ast_gen: &'g ASTGen<'a>, tokens: &'g [Token<'a>]) -> Self {
+        Packrat {
+            ast_gen,
+            tokens,
+            nodes: vec![],
+            memo: HashMap::new(),
+            active: HashMap::new(),
+            cut: usize::MAX,
+            expanded: 0,
+            furthest: 0,
+            expected: vec![],
+        }
+    }
+
+    fn fail(&mut self, pos: usize, expected: String) {
+        if pos > self.furthest {
+            self.furthest = pos;
+            self.expected.clear();
+        }
+
+        if pos == self.furthest && !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+
+    fn push(&mut self, rule: &'a str, children: Vec<Part>, start: usize, end: usize) -> usize {
+        self.nodes.push(Assembled {
+            rule,
+            children,
+            start,
+            end,
+        });
+
+        self.nodes.len() - 1
+    }
+
+    fn end(&self, node: usize) -> usize {
+        self.nodes[node].end
+    }
+
+    /// The AST of the node, the children of the assemblers with a `#` are
+    /// moved to the parent like in `Node::new`.
+    fn node(&self, node: usize) -> Node<'a> {
+        let assembled = &self.nodes[node];
+        let mut children = vec![];
+        self.children(node, &mut children);
+
+        Node {
+            rule: assembled.rule,
+            children,
+            start: assembled.start,
+            end: assembled.end,
+        }
+    }
+
+    fn children(&self, node: usize, out: &mut Vec<Child<'a>>) {
+        stacker::maybe_grow(RED_ZONE, STACK_SIZE, || {
+            for part in &self.nodes[node].children {
+                match *part {
+                    Part::Node(i) if self.nodes[i].rule.contains('#') => self.children(i, out),
+                    Part::Node(i) => out.push(Child::Node(self.node(i))),
+                    Part::Token(i) => out.push(Child::Token(self.tokens[i].clone())),
+                }
+            }
+        })
+    }
+
+    fn assemble(&mut self, rule: &'a str, pos: usize) -> Option<usize> {
+        if let Some(res) = self.memo.get(&(rule, pos)) {
+            return *res;
+        }
+
+        // Expanding the same rule again without consuming any token would
+        // never end, so this path is abandoned.
+        if let Some(&depth) = self.active.get(&(rule, pos)) {
+            self.cut = self.cut.min(depth);
+            return None;
+        }
+
+        let depth = self.active.len();
+        let cut = std::mem::replace(&mut self.cut, usize::MAX);
+        self.active.insert((rule, pos), depth);
+        self.expanded += 1;
+
+        let res = stacker::maybe_grow(RED_ZONE, STACK_SIZE, || self.expand(rule, pos));
+
+        self.active.remove(&(rule, pos));
+
+        // A result that depends on an assembler abandoned below this one
+        // could be different when tried again out of it, so it isn't kept.
+        if self.cut >= depth {
+            self.memo.insert((rule, pos), res);
+        }
+
+        self.cut = self.cut.min(cut);
+        res
+    }
+
+    fn expand(&mut self, rule: &'a str, pos: usize) -> Option<usize> {
+        if let Some(operators) = self.ast_gen.operators(rule) {
+            return self.pratt(rule, &operators, pos, 0);
+        }
+
+        for (name, arguments) in &self.ast_gen.assemblers {
+            if *name != rule {
+                continue;
+            }
+
+            if let Some((children, end)) = self.assemble_sequence(arguments, pos) {
+                return Some(self.push(rule, children, pos, end));
+            }
+        }
+
+        None
+    }
+
+    /// Assembles the operand at `pos` and the operators after it with a
+    /// level from `min` up.
+    fn pratt(
+        &mut self,
+        rule: &'a str,
+        operators: &Operators<'a, 'g>,
+        pos: usize,
+        min: usize,
+    ) -> Option<usize> {
+        stacker::maybe_grow(RED_ZONE, STACK_SIZE, || {
+            self.pratt_operators(rule, operators, pos, min)
+        })
+    }
+
+    fn pratt_operators(
+        &mut self,
+        rule: &'a str,
+        operators: &Operators<'a, 'g>,
+        pos: usize,
+        min: usize,
+    ) -> Option<usize> {
+        let mut lhs = self.pratt_operand(rule, operators, pos)?;
+        // The level of the last non-associative operator, that can't be
+        // followed by another one of the same level.
+        let mut nonassoc = None;
+
+        loop {
+            let at = self.end(lhs);
+            let found = self.tokens.get(at).and_then(|token| {
+                operators
+                    .binary
+                    .iter()
+                    .find(|(operator, ..)| self.ast_gen.token_match(operator, token))
+            });
+
+            let (_, level, assoc) = match found {
+                Some(found) => *found,
+                None => {
+                    for (operator, ..) in &operators.binary {
+                        self.fail(at, operator.to_string());
+                    }
+
+                    break;
+                }
+            };
+
+            if level < min || nonassoc == Some(level) {
+                break;
+            }
+
+            let next = if assoc == Assoc::Right {
+                level
+            } else {
+                level + 1
+            };
+            let rhs = match self.pratt(rule, operators, at + 1, next) {
+                Some(rhs) => rhs,
+                None => break,
+            };
+
+            let (start, end) = (self.nodes[lhs].start, self.end(rhs));
+            let children = vec![Part::Node(lhs), Part::Token(at), Part::Node(rhs)];
+
+            lhs = self.push(rule, children, start, end);
+            nonassoc = (assoc == Assoc::NonAssoc).then_some(level);
+        }
+
+        Some(lhs)
+    }
+
+    /// A prefix operator with its operand, or the first operand option that
+    /// matches at `pos`.
+    fn pratt_operand(
+        &mut self,
+        rule: &'a str,
+        operators: &Operators<'a, 'g>,
+        pos: usize,
+    ) -> Option<usize> {
+        if let Some(token) = self.tokens.get(pos) {
+            for (operator, level) in &operators.prefix {
+                if !self.ast_gen.token_match(operator, token) {
+                    continue;
+                }
+
+                let operand = match self.pratt(rule, operators, pos + 1, *level) {
+                    Some(operand) => operand,
+                    None => continue,
+                };
+                let end = self.end(operand);
+                let children = vec![Part::Token(pos), Part::Node(operand)];
+
+                return Some(self.push(rule, children, pos, end));
+            }
+        }
+
+        for (operator, _) in &operators.prefix {
+            self.fail(pos, operator.to_string());
+        }
+
+        for arguments in &operators.operands {
+            if let Some((children, end)) = self.assemble_sequence(arguments, pos) {
+                return Some(self.push(rule, children, pos, end));
+            }
+        }
+
+        None
+    }
+
+    fn assemble_sequence(
+        &mut self,
+        arguments: &[Argument<'a>],
+        pos: usize,
+    ) -> Option<(Vec<Part>, usize)> {
+        let mut children = vec![];
+        let mut pos = pos;
+
+        for argument in arguments {
+            match *argument {
+                Argument::Empty => (),
+                Argument::Assembler(name) => {
+                    let node = self.assemble(name, pos)?;
+                    pos = self.end(node);
+                    children.push(Part::Node(node));
+                }
+                Argument::Symbol("eof") => {
+                    if pos != self.tokens.len() {
+                        self.fail(pos, ":eof:".to_string());
+                        return None;
+                    }
+                }
+                Argument::Symbol("eol") => {
+                    if pos != self.tokens.len()
+                        && !self.ast_gen.symbol_match("nwl", self.tokens[pos].content)
+                    {
+                        self.fail(pos, ":eol:".to_string());
+                        return None;
+                    }
+                }
+                _ => {
+                    match self.tokens.get(pos) {
+                        Some(token) if self.ast_gen.token_match(argument, token) => (),
+                        _ => {
+                            self.fail(pos, argument.to_string());
+                            return None;
+                        }
+                    }
+
+                    children.push(Part::Token(pos));
+                    pos += 1;
+                }
+            }
+        }
+
+        Some((children, pos))
+    }
+}
@@ -0,0 +1,139 @@
+use regex::Regex;
+
+use crate::TokenKind;
+
+/// The type of the tokens of line comments declared in TFLAC.
+pub const LINE_COMMENT: &str = "LINE_COMMENT";
+/// The type of the tokens of block comments declared in TFLAC.
+pub const BLOCK_COMMENT: &str = "BLOCK_COMMENT";
+
+/// A comment rule, searched before the searchers.
+///
+/// A line comment starts with a match of `open` and goes up to the end of
+/// the line, a block comment goes up to the first match of `close`. The
+/// comments are dropped, unless `TFLA::keep_comments` is enabled, then they
+/// become tokens of type `name`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Comment<'a> {
+    pub name: &'a str,
+    pub open: &'a str,
+    pub close: Option<&'a str>,
+    /// A block comment opened inside another one must be closed too, like
+    /// `/* /* */ */` in Rust.
+    pub nested: bool,
+}
+
+impl<'a> Comment<'a> {
+    pub fn line(name: &'a str, open: &'a str) -> Self {
+        Comment {
+            name,
+            open,
+            close: None,
+            nested: false,
+        }
+    }
+
+    pub fn block(name: &'a str, open: &'a str, close: &'a str) -> Self {
+        Comment {
+            name,
+            open,
+            close: Some(close),
+            nested: false,
+        }
+    }
+
+    pub fn nested(name: &'a str, open: &'a str, close: &'a str) -> Self {
+        Comment {
+            nested: true,
+            ..Comment::block(name, open, close)
+        }
+    }
+}
+
+/// A [`Comment`] with its regexes compiled.
+pub(crate) struct CommentRule<'a> {
+    pub comment: Comment<'a>,
+    pub kind: TokenKind,
+    open: Regex,
+    close: Option<Regex>,
+    /// The close regex anchored at the start, to count the nesting depth.
+    close_at: Option<Regex>,
+}
+
+impl<'a> CommentRule<'a> {
+    pub fn new(comment: Comment<'a>, kind: TokenKind) -> Result<Self, regex::Error> {
+        let open = Regex::new(&format!("^(?:{})", comment.open))?;
+        let close = match comment.close {
+            Some(close) => Some(Regex::new(close)?),
+            None => None,
+        };
+        let close_at = match comment.close {
+            Some(close) if comment.nested => Some(Regex::new(&format!("^(?:{})", close))?),
+            _ => None,
+        };
+
+        Ok(CommentRule {
+            comment,
+            kind,
+            open,
+            close,
+            close_at,
+        })
+    }
+
+    /// Finds the end of the comment that starts at the start of the code,
+    /// and if it was closed.
+    ///
+    /// A block comment without an end goes up to the end of the code.
+    pub fn find(&self, code: &str) -> Option<(usize, bool)> {
+        let open = self.open_at(code)?;
+        let rest = &code[open..];
+
+        let (len, closed) = match (&self.close, &self.close_at) {
+            (_, Some(close_at)) => self.find_nested(rest, close_at),
+            (Some(close), None) => close
+                .find(rest)
+                .map_or((rest.len(), false), |m| (m.end(), true)),
+            (None, _) => match rest.find('\n') {
+                Some(i) if rest[..i].ends_with('\r') => (i - 1, true),
+                Some(i) => (i, true),
+                None => (rest.len(), true),
+            },
+        };
+
+        Some((open + len, closed))
+    }
+
+    /// The end of the open match at the start of the code, an empty match
+    /// don't open a comment.
+    fn open_at(&self, code: &str) -> Option<usize> {
+        self.open.find(code).map(|m| m.end()).filter(|end| *end > 0)
+    }
+
+    /// Finds the close of a nested comment, counting each open found before
+    /// it as one more level.
+    fn find_nested(&self, code: &str, close_at: &Regex) -> (usize, bool) {
+        let mut depth = 1;
+        let mut i = 0;
+
+        while i < code.len() {
+            let rest = &code[i..];
+
+            if let Some(end) = close_at.find(rest).map(|m| m.end()).filter(|end| *end > 0) {
+                depth -= 1;
+                i += end;
+
+                if depth == 0 {
+                    return (i, true);
+                }
+            } else if let Some(end) = self.open_at(rest) {
+                depth += 1;
+                i += end;
+            } else {
+                i += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+
+        (code.len(), false)
+    }
+}
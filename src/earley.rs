@@ -0,0 +1,553 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{ASTGen, Argument, AstError, Child, Node};
+use crate::Token;
+
+/// A child of a [`Packed`] alternative, an index in [`Forest::nodes`] or in
+/// the tokens.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ForestChild {
+    Node(usize),
+    Token(usize),
+}
+
+/// One way an assembler matches the tokens of a [`ForestNode`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packed {
+    /// The index of the option in `TFLA::assemblers`.
+    pub assembler: usize,
+    pub children: Vec<ForestChild>,
+}
+
+/// The assembler `rule` matching the tokens from `start` to `end`, with all
+/// the ways it matches. With more than one alternative the tokens are
+/// ambiguous.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForestNode<'a> {
+    pub rule: &'a str,
+    pub start: usize,
+    pub end: usize,
+    pub alternatives: Vec<Packed>,
+}
+
+/// A shared packed parse forest, with every tree of an ambiguous grammar.
+///
+/// The same assembler over the same tokens is a single node, shared by all
+/// the trees that have it, so the forest stays small even when the number
+/// of trees grows exponentially.
+#[derive(Debug, Clone)]
+pub struct Forest<'a> {
+    pub nodes: Vec<ForestNode<'a>>,
+    pub root: usize,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Forest<'a> {
+    /// If the tokens have more than one tree, some node reachable from the
+    /// root has more than one alternative.
+    pub fn is_ambiguous(&self) -> bool {
+        let mut seen = HashSet::from([self.root]);
+        let mut pending = vec![self.root];
+
+        while let Some(node) = pending.pop() {
+            let alternatives = &self.nodes[node].alternatives;
+
+            if alternatives.len() > 1 {
+                return true;
+            }
+
+            for packed in alternatives {
+                for child in &packed.children {
+                    if let ForestChild::Node(n) = *child {
+                        if seen.insert(n) {
+                            pending.push(n);
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// The number of trees, saturating at `usize::MAX`.
+    pub fn count(&self) -> usize {
+        let mut count = Count {
+            memo: HashMap::new(),
+            path: vec![],
+            cut: usize::MAX,
+        };
+
+        self.count_node(self.root, &mut count)
+    }
+
+    fn count_node(&self, node: usize, count: &mut Count) -> usize {
+        if let Some(&trees) = count.memo.get(&node) {
+            return trees;
+        }
+
+        let depth = count.path.len();
+        let cut = std::mem::replace(&mut count.cut, usize::MAX);
+        count.path.push(node);
+
+        let mut res: usize = 0;
+
+        'alternatives: for packed in &self.nodes[node].alternatives {
+            let mut trees: usize = 1;
+
+            for child in &packed.children {
+                if let ForestChild::Node(n) = *child {
+                    // A tree can't have itself, see `alternatives`.
+                    if let Some(i) = count.path.iter().position(|p| *p == n) {
+                        count.cut = count.cut.min(i);
+                        continue 'alternatives;
+                    }
+
+                    trees = trees.saturating_mul(self.count_node(n, count));
+                }
+            }
+
+            res = res.saturating_add(trees);
+        }
+
+        count.path.pop();
+
+        // Without the alternatives skipped for a node above this one, the
+        // number is the same wherever the node is.
+        if count.cut >= depth {
+            count.memo.insert(node, res);
+        }
+
+        count.cut = count.cut.min(cut);
+        res
+    }
+
+    /// The alternatives of the node that don't go back to a node in the path,
+    /// since a tree can't have itself.
+    fn alternatives(&self, node: usize, path: &[usize]) -> Vec<&Packed> {
+        self.nodes[node]
+            .alternatives
+            .iter()
+            .filter(|packed| {
+                !packed
+                    .children
+                    .iter()
+                    .any(|child| matches!(child, ForestChild::Node(n) if path.contains(n)))
+            })
+            .collect()
+    }
+
+    /// Every tree of the forest. They can be a lot, see `count`.
+    pub fn trees(&self) -> Vec<Node<'a>> {
+        self.trees_of(self.root, &mut vec![])
+    }
+
+    fn trees_of(&self, node: usize, path: &mut Vec<usize>) -> Vec<Node<'a>> {
+        path.push(node);
+
+        let ForestNode {
+            rule, start, end, ..
+        } = self.nodes[node];
+        let mut res = vec![];
+
+        for packed in self.alternatives(node, path) {
+            let mut partial: Vec<Vec<Child<'a>>> = vec![vec![]];
+
+            for child in &packed.children {
+                let options: Vec<Child<'a>> = match *child {
+                    ForestChild::Node(n) => self
+                        .trees_of(n, path)
+                        .into_iter()
+                        .map(Child::Node)
+                        .collect(),
+                    ForestChild::Token(t) => vec![Child::Token(self.tokens[t].clone())],
+                };
+
+                partial = partial
+                    .iter()
+                    .flat_map(|children| {
+                        options.iter().map(move |option| {
+                            let mut children = children.clone();
+                            children.push(option.clone());
+                            children
+                        })
+                    })
+                    .collect();
+            }
+
+            res.extend(
+                partial
+                    .into_iter()
+                    .map(|children| Node::new(rule, children, start, end)),
+            );
+        }
+
+        path.pop();
+        res
+    }
+
+    /// Builds one tree, `choose` gets each node with more than one
+    /// alternative and returns the index of the alternative to use.
+    pub fn disambiguate<F>(&self, mut choose: F) -> Node<'a>
+    where
+        F: FnMut(&ForestNode<'a>) -> usize,
+    {
+        // The tokens matched, so some tree doesn't have itself.
+        self.choose_tree(self.root, &mut choose, &mut vec![])
+            .expect("the forest has a tree")
+    }
+
+    /// The tree of the node, or none when every alternative goes back to a
+    /// node in the path, like `<b>` in `<a> : <b>` and `<b> : <a>`.
+    fn choose_tree<F>(&self, node: usize, choose: &mut F, path: &mut Vec<usize>) -> Option<Node<'a>>
+    where
+        F: FnMut(&ForestNode<'a>) -> usize,
+    {
+        path.push(node);
+
+        let forest_node = &self.nodes[node];
+        let mut alternatives = self.alternatives(node, path);
+
+        if alternatives.len() > 1 {
+            // The choice is an index in all the alternatives, it's tried
+            // first, the others are tried if it can only make a cycle.
+            let chosen = &forest_node.alternatives[choose(forest_node)];
+
+            if let Some(i) = alternatives.iter().position(|packed| *packed == chosen) {
+                let packed = alternatives.remove(i);
+                alternatives.insert(0, packed);
+            }
+        }
+
+        let mut tree = None;
+
+        'alternatives: for packed in alternatives {
+            let mut children = vec![];
+
+            for child in &packed.children {
+                match *child {
+                    ForestChild::Node(n) => match self.choose_tree(n, choose, path) {
+                        Some(node) => children.push(Child::Node(node)),
+                        None => continue 'alternatives,
+                    },
+                    ForestChild::Token(t) => children.push(Child::Token(self.tokens[t].clone())),
+                }
+            }
+
+            tree = Some(Node::new(
+                forest_node.rule,
+                children,
+                forest_node.start,
+                forest_node.end,
+            ));
+            break;
+        }
+
+        path.pop();
+        tree
+    }
+
+    /// The tree with the first alternative of every node, the one with the
+    /// options declared first.
+    pub fn tree(&self) -> Node<'a> {
+        self.disambiguate(|_| 0)
+    }
+}
+
+/// The state of one [`Forest::count`].
+struct Count {
+    /// The trees of each node counted.
+    memo: HashMap<usize, usize>,
+    /// The nodes being counted.
+    path: Vec<usize>,
+    /// The lowest index in `path` of a node that made an alternative be
+    /// skipped, since the last node was counted.
+    cut: usize,
+}
+
+/// An option of an assembler with the dot before the argument `dot`,
+/// started at the token `origin`.
+type Item = (usize, usize, usize);
+
+/// The state of one [`ASTGen::generate_forest`].
+struct Earley<'g, 'a> {
+    ast_gen: &'g ASTGen<'a>,
+    tokens: &'g [Token<'a>],
+    /// The arguments of each assembler, without the ε.
+    arguments: Vec<Vec<Argument<'a>>>,
+    /// The items of each position, in the order they were found.
+    sets: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+    nodes: Vec<ForestNode<'a>>,
+    node_ids: HashMap<(&'a str, usize, usize), usize>,
+}
+
+impl<'a> ASTGen<'a> {
+    /// Assembles the tokens with an Earley parser, that accepts any grammar,
+    /// even the ambiguous and left recursive ones, and returns all the trees
+    /// that match them.
+    pub fn generate_forest(
+        &self,
+        tokens: &[Token<'a>],
+        root: &'a str,
+    ) -> Result<Forest<'a>, AstError> {
+        if !self.assemblers.iter().any(|(name, _)| *name == root) {
+            return Err(AstError::UnknownRule(root.to_string()));
+        }
+
+        let mut earley = Earley {
+            ast_gen: self,
+            tokens,
+            arguments: self
+                .assemblers
+                .iter()
+                .map(|(_, arguments)| {
+                    arguments
+                        .iter()
+                        .filter(|a| **a != Argument::Empty)
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+            sets: vec![vec![]; tokens.len() + 1],
+            seen: vec![HashSet::new(); tokens.len() + 1],
+            nodes: vec![],
+            node_ids: HashMap::new(),
+        };
+
+        earley.recognize(root);
+
+        if !earley.completed(root, 0, tokens.len()) {
+            return Err(earley.error(root));
+        }
+
+        let root = earley.node(root, 0, tokens.len());
+
+        Ok(Forest {
+            nodes: earley.nodes,
+            root,
+            tokens: tokens.to_vec(),
+        })
+    }
+}
+
+impl<'g, 'a> Earley<'g, 'a> {
+    fn add(&mut self, pos: usize, item: Item) {
+        if self.seen[pos].insert(item) {
+            self.sets[pos].push(item);
+        }
+    }
+
+    fn rule(&self, p: usize) -> &'a str {
+        self.ast_gen.assemblers[p].0
+    }
+
+    /// If a symbol that don't consume tokens matches at the position.
+    fn zero_width(&self, argument: Argument<'a>, pos: usize) -> Option<bool> {
+        match argument {
+            Argument::Symbol("eof") => Some(pos == self.tokens.len()),
+            Argument::Symbol("eol") => Some(
+                self.tokens
+                    .get(pos)
+                    .is_none_or(|token| self.ast_gen.symbol_match("nwl", token.content)),
+            ),
+            _ => None,
+        }
+    }
+
+    fn token_match(&self, argument: Argument<'a>, pos: usize) -> bool {
+        self.tokens
+            .get(pos)
+            .is_some_and(|token| self.ast_gen.token_match(&argument, token))
+    }
+
+    fn recognize(&mut self, root: &'a str) {
+        for p in 0..self.arguments.len() {
+            if self.rule(p) == root {
+                self.add(0, (p, 0, 0));
+            }
+        }
+
+        for pos in 0..self.sets.len() {
+            // The assemblers completed without consuming tokens here, an
+            // item that waits for one of them after they completed is
+            // advanced when it's predicted.
+            let mut empty: HashSet<&'a str> = HashSet::new();
+            let mut i = 0;
+
+            while i < self.sets[pos].len() {
+                let (p, dot, origin) = self.sets[pos][i];
+                i += 1;
+
+                let argument = match self.arguments[p].get(dot) {
+                    Some(argument) => *argument,
+                    None => {
+                        let rule = self.rule(p);
+
+                        if origin == pos {
+                            empty.insert(rule);
+                        }
+
+                        let mut j = 0;
+
+                        while j < self.sets[origin].len() {
+                            let (q, d, o) = self.sets[origin][j];
+                            j += 1;
+
+                            if self.arguments[q].get(d) == Some(&Argument::Assembler(rule)) {
+                                self.add(pos, (q, d + 1, o));
+                            }
+                        }
+
+                        continue;
+                    }
+                };
+
+                match argument {
+                    Argument::Assembler(name) => {
+                        for q in 0..self.arguments.len() {
+                            if self.rule(q) == name {
+                                self.add(pos, (q, 0, pos));
+                            }
+                        }
+
+                        if empty.contains(name) {
+                            self.add(pos, (p, dot + 1, origin));
+                        }
+                    }
+                    _ => match self.zero_width(argument, pos) {
+                        Some(true) => self.add(pos, (p, dot + 1, origin)),
+                        Some(false) => (),
+                        None if self.token_match(argument, pos) => {
+                            self.add(pos + 1, (p, dot + 1, origin))
+                        }
+                        None => (),
+                    },
+                }
+            }
+        }
+    }
+
+    /// If the assembler matches the tokens from `start` to `end`.
+    fn completed(&self, rule: &str, start: usize, end: usize) -> bool {
+        self.sets[end].iter().any(|&(p, dot, origin)| {
+            origin == start && dot == self.arguments[p].len() && self.rule(p) == rule
+        })
+    }
+
+    /// The error at the last position reached, with the arguments expected
+    /// there.
+    fn error(&self, root: &str) -> AstError {
+        let index = (0..self.sets.len())
+            .rev()
+            .find(|&pos| !self.sets[pos].is_empty())
+            .unwrap_or(0);
+        let mut expected: Vec<String> = vec![];
+
+        for &(p, dot, _) in &self.sets[index] {
+            let argument = match self.arguments[p].get(dot) {
+                Some(Argument::Assembler(_)) | None => continue,
+                Some(argument) => argument.to_string(),
+            };
+
+            if !expected.contains(&argument) {
+                expected.push(argument);
+            }
+        }
+
+        if self.completed(root, 0, index) {
+            expected.push("end of tokens".to_string());
+        }
+
+        AstError::Unexpected {
+            index,
+            found: self.tokens.get(index).map(|t| t.content.to_string()),
+            expected,
+        }
+    }
+
+    /// The forest node of the assembler matching from `start` to `end`.
+    fn node(&mut self, rule: &'a str, start: usize, end: usize) -> usize {
+        if let Some(id) = self.node_ids.get(&(rule, start, end)) {
+            return *id;
+        }
+
+        // The node exists before its alternatives, so a cycle goes back to
+        // it instead of never ending.
+        let id = self.nodes.len();
+        self.node_ids.insert((rule, start, end), id);
+        self.nodes.push(ForestNode {
+            rule,
+            start,
+            end,
+            alternatives: vec![],
+        });
+
+        let mut alternatives = vec![];
+
+        for p in 0..self.arguments.len() {
+            if self.rule(p) != rule
+                || !self.seen[end].contains(&(p, self.arguments[p].len(), start))
+            {
+                continue;
+            }
+
+            for children in self.split(p, self.arguments[p].len(), start, end) {
+                alternatives.push(Packed {
+                    assembler: p,
+                    children,
+                });
+            }
+        }
+
+        self.nodes[id].alternatives = alternatives;
+        id
+    }
+
+    /// The ways the arguments of `p` before `dot` match the tokens from
+    /// `start` to `end`.
+    fn split(&mut self, p: usize, dot: usize, start: usize, end: usize) -> Vec<Vec<ForestChild>> {
+        if dot == 0 {
+            return if start == end { vec![vec![]] } else { vec![] };
+        }
+
+        let before = (p, dot - 1, start);
+        let argument = self.arguments[p][dot - 1];
+        let mut res = vec![];
+
+        match argument {
+            Argument::Assembler(name) => {
+                for mid in start..=end {
+                    if !self.seen[mid].contains(&before) || !self.completed(name, mid, end) {
+                        continue;
+                    }
+
+                    let node = self.node(name, mid, end);
+
+                    for mut children in self.split(p, dot - 1, start, mid) {
+                        children.push(ForestChild::Node(node));
+                        res.push(children);
+                    }
+                }
+            }
+            _ => match self.zero_width(argument, end) {
+                Some(true) if self.seen[end].contains(&before) => {
+                    res = self.split(p, dot - 1, start, end)
+                }
+                Some(_) => (),
+                None if end > start
+                    && self.seen[end - 1].contains(&before)
+                    && self.token_match(argument, end - 1) =>
+                {
+                    for mut children in self.split(p, dot - 1, start, end - 1) {
+                        children.push(ForestChild::Token(end - 1));
+                        res.push(children);
+                    }
+                }
+                None => (),
+            },
+        }
+
+        res
+    }
+}
@@ -0,0 +1,327 @@
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::{
+    Assoc, Comment, Converter, LrAction, LrState, LrTable, ModeAction, Offside, Precedence,
+    SearcherError, TFLA,
+};
+
+#[derive(Debug)]
+pub enum ExaustError {
+    /// The file can't be read or isn't a valid gzip stream.
+    Io(io::Error),
+    /// A line of the exaust don't follow the `se`/`sy`/`as`/`co`/`mo`/`ac`/`cv`/
+    /// `kw`/`of`/`lr`/`pa`/`pr` syntax, or an `lr` line goes to a state or assembler
+    /// that don't exists.
+    Syntax { line: usize, content: String },
+    /// The regex of a searcher, symbol or comment can't be compiled.
+    Regex { name: String, error: regex::Error },
+    /// A searcher is named ERROR, INDENT or DEDENT.
+    Reserved { name: String },
+    /// A mode has a searcher that don't exists.
+    UnknownSearcher { mode: String, searcher: String },
+}
+
+impl fmt::Display for ExaustError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExaustError::Io(e) => write!(f, "Can't read the exaust: {}", e),
+            ExaustError::Syntax { line, content } => {
+                write!(f, "Invalid exaust line \"{}\". |{}|", content, line)
+            }
+            ExaustError::Regex { name, error } => {
+                write!(f, "Invalid regex in [{}]: {}", name, error)
+            }
+            ExaustError::Reserved { name } => {
+                write!(f, "The Searcher [{}] have a name reserved by TFLA.", name)
+            }
+            ExaustError::UnknownSearcher { mode, searcher } => {
+                write!(
+                    f,
+                    "The mode {} have the searcher [{}], but it's don't exists.",
+                    mode, searcher
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExaustError {}
+
+impl From<io::Error> for ExaustError {
+    fn from(e: io::Error) -> Self {
+        ExaustError::Io(e)
+    }
+}
+
+/// The searchers, symbols, assemblers, comments, modes, converters,
+/// keywords, offside rule, precedences, LR table and parser of an exaust, the first three in the
+/// same shape taken by `TFLA::new`.
+struct Config<'a> {
+    searchers: Vec<(&'a str, &'a str)>,
+    symbols: Vec<(&'a str, &'a str)>,
+    assemblers: Vec<(&'a str, Vec<&'a str>)>,
+    comments: Vec<Comment<'a>>,
+    modes: Vec<(&'a str, Vec<&'a str>)>,
+    actions: Vec<(&'a str, ModeAction<'a>)>,
+    converters: Vec<(&'a str, Converter)>,
+    keywords: Vec<(&'a str, Vec<&'a str>)>,
+    offside: Option<Offside>,
+    precedences: Vec<Precedence<'a>>,
+    lr_table: Option<LrTable<'a>>,
+    earley: bool,
+}
+
+/// A decompressed .exaust.tfla file, as written by `tfla-cc --compile`.
+///
+/// The TFLA instances built from it borrow the searcher, symbol and
+/// assembler names, so the `Exaust` must outlive them.
+pub struct Exaust {
+    source: String,
+}
+
+impl Exaust {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ExaustError> {
+        Exaust::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ExaustError> {
+        let mut source = String::new();
+
+        GzDecoder::new(bytes).read_to_string(&mut source)?;
+
+        Ok(Exaust { source })
+    }
+
+    /// Wraps an already decompressed exaust, like the output of `tfla-cc -d`.
+    pub fn from_source(source: String) -> Self {
+        Exaust { source }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn parse(&self) -> Result<Config<'_>, ExaustError> {
+        let mut searchers: Vec<(&str, &str)> = vec![];
+        let mut symbols: Vec<(&str, &str)> = vec![];
+        let mut assemblers: Vec<(&str, Vec<&str>)> = vec![];
+        let mut comments: Vec<Comment> = vec![];
+        let mut modes: Vec<(&str, Vec<&str>)> = vec![];
+        let mut actions: Vec<(&str, ModeAction)> = vec![];
+        let mut converters: Vec<(&str, Converter)> = vec![];
+        let mut keywords: Vec<(&str, Vec<&str>)> = vec![];
+        let mut offside: Option<Offside> = None;
+        let mut lr_lines: Vec<(usize, &str)> = vec![];
+        let mut lr_table = LrTable::default();
+        let mut earley = false;
+        let mut precedences: Vec<Precedence> = vec![];
+
+        for (i, line) in self.source.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let syntax_error = || ExaustError::Syntax {
+                line: i + 1,
+                content: line.to_string(),
+            };
+
+            let mut parts = line.splitn(3, ' ');
+            let prefix = parts.next().ok_or_else(syntax_error)?;
+            let name = parts
+                .next()
+                .filter(|n| !n.is_empty())
+                .ok_or_else(syntax_error)?;
+            let arbitrary = parts.next().unwrap_or("");
+
+            match prefix {
+                "se" => searchers.push((name, arbitrary)),
+                "sy" => {
+                    Regex::new(arbitrary).map_err(|error| ExaustError::Regex {
+                        name: name.to_string(),
+                        error,
+                    })?;
+
+                    symbols.push((name, arbitrary));
+                }
+                "as" => {
+                    let arguments: Vec<&str> =
+                        arbitrary.split(' ').filter(|a| !a.is_empty()).collect();
+
+                    for a in &arguments {
+                        if !["as-", "se-", "sy-", "li-"]
+                            .iter()
+                            .any(|p| a.starts_with(p))
+                        {
+                            return Err(syntax_error());
+                        }
+                    }
+
+                    assemblers.push((name, arguments));
+                }
+                "co" => {
+                    // The open and close regexes are split by a space, so
+                    // they can't have spaces themselves.
+                    let mut regexes = arbitrary.split(' ').filter(|r| !r.is_empty());
+                    let open = regexes.next().ok_or_else(syntax_error)?;
+
+                    comments.push(match (regexes.next(), regexes.next(), regexes.next()) {
+                        (None, _, _) => Comment::line(name, open),
+                        (Some(close), None, _) => Comment::block(name, open, close),
+                        (Some(close), Some("nested"), None) => Comment::nested(name, open, close),
+                        _ => return Err(syntax_error()),
+                    });
+                }
+                "mo" => modes.push((
+                    name,
+                    arbitrary.split(' ').filter(|s| !s.is_empty()).collect(),
+                )),
+                "ac" => {
+                    let action = match arbitrary.split_once(' ') {
+                        Some(("push", mode)) => ModeAction::Push(mode),
+                        Some(("switch", mode)) => ModeAction::Switch(mode),
+                        None if arbitrary == "pop" => ModeAction::Pop,
+                        _ => return Err(syntax_error()),
+                    };
+
+                    actions.push((name, action));
+                }
+                "cv" => {
+                    let converter = Converter::from_name(arbitrary).ok_or_else(syntax_error)?;
+
+                    converters.push((name, converter));
+                }
+                "of" if name == "tab_width" => {
+                    let tab_width = arbitrary.parse().map_err(|_| syntax_error())?;
+
+                    offside = Some(Offside::new(tab_width));
+                }
+                "kw" => keywords.push((
+                    name,
+                    arbitrary.split(' ').filter(|k| !k.is_empty()).collect(),
+                )),
+                "pa" if name == "earley" => earley = true,
+                "pr" => {
+                    let assoc = Assoc::from_name(name).ok_or_else(syntax_error)?;
+                    let operators: Vec<&str> =
+                        arbitrary.split(' ').filter(|o| !o.is_empty()).collect();
+
+                    if operators.is_empty()
+                        || !operators
+                            .iter()
+                            .all(|o| ["se-", "sy-", "li-"].iter().any(|p| o.starts_with(p)))
+                    {
+                        return Err(syntax_error());
+                    }
+
+                    precedences.push(Precedence::new(assoc, &operators));
+                }
+                "lr" => {
+                    let state = LrState::parse(arbitrary)
+                        .filter(|_| name.parse() == Ok(lr_table.states.len()))
+                        .ok_or_else(syntax_error)?;
+
+                    lr_lines.push((i + 1, line));
+                    lr_table.states.push(state);
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        // The states and assemblers are checked after all the lines, since a
+        // state can go to the states after it.
+        for ((line, content), state) in lr_lines.iter().zip(&lr_table.states) {
+            let states = lr_table.states.len();
+            let valid_action = |action: &LrAction| match *action {
+                LrAction::Shift(s) => s < states,
+                LrAction::Reduce(r) => r < assemblers.len(),
+                LrAction::Accept => true,
+            };
+
+            if !state.actions.iter().all(|(_, a)| valid_action(a))
+                || !state.gotos.iter().all(|(_, g)| *g < states)
+            {
+                return Err(ExaustError::Syntax {
+                    line: *line,
+                    content: content.to_string(),
+                });
+            }
+        }
+
+        Ok(Config {
+            searchers,
+            symbols,
+            assemblers,
+            comments,
+            modes,
+            actions,
+            converters,
+            keywords,
+            offside,
+            precedences,
+            lr_table: (!lr_table.states.is_empty()).then_some(lr_table),
+            earley,
+        })
+    }
+}
+
+impl<'a> TFLA<'a> {
+    /// Configures a TFLA with the searchers, symbols, assemblers, comments,
+    /// modes, converters, keywords, offside rule, precedences, LR table and
+    /// parser of a compiled TFLAC file.
+    pub fn from_exaust(exaust: &'a Exaust, ignore_spaces: bool) -> Result<Self, ExaustError> {
+        let config = exaust.parse()?;
+
+        let mut tfla = TFLA::try_new(config.searchers, config.assemblers, ignore_spaces).map_err(
+            |(name, error)| match error {
+                SearcherError::Regex(error) => ExaustError::Regex {
+                    name: name.to_string(),
+                    error,
+                },
+                SearcherError::Reserved => ExaustError::Reserved {
+                    name: name.to_string(),
+                },
+            },
+        )?;
+        tfla.symbols = config.symbols;
+        tfla.offside = config.offside;
+        tfla.precedences = config.precedences;
+        tfla.lr_table = config.lr_table;
+        tfla.earley = config.earley;
+
+        for comment in config.comments {
+            tfla.add_comment(comment)
+                .map_err(|error| ExaustError::Regex {
+                    name: comment.name.to_string(),
+                    error,
+                })?;
+        }
+
+        for (mode, searchers) in config.modes {
+            tfla.add_mode(mode, &searchers)
+                .map_err(|searcher| ExaustError::UnknownSearcher {
+                    mode: mode.to_string(),
+                    searcher: searcher.to_string(),
+                })?;
+        }
+
+        for (searcher, action) in config.actions {
+            tfla.add_action(searcher, action);
+        }
+
+        for (searcher, converter) in config.converters {
+            tfla.add_converter(searcher, converter);
+        }
+
+        for (searcher, keywords) in config.keywords {
+            tfla.add_keywords(searcher, &keywords);
+        }
+
+        Ok(tfla)
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::offside::{DEDENT, INDENT};
+use crate::ERROR;
+
+/// The type of a token interned as a number, so it's compared without
+/// comparing strings. The names are in `TFLA::kind_name`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TokenKind(pub u16);
+
+impl TokenKind {
+    pub const ERROR: TokenKind = TokenKind(0);
+    pub const INDENT: TokenKind = TokenKind(1);
+    pub const DEDENT: TokenKind = TokenKind(2);
+}
+
+/// Where a token is in the source, `start` and `end` are byte offsets, `line`
+/// and `column` where it starts, counting chars from 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// The token types made by TFLA, with the kinds 0, 1 and 2, a searcher can't
+/// have these names.
+pub(crate) const RESERVED: [&str; 3] = [ERROR, INDENT, DEDENT];
+
+/// The names of the token types, each one with the kind of its index.
+#[derive(Debug, Clone)]
+pub(crate) struct Kinds<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, TokenKind>,
+}
+
+impl<'a> Kinds<'a> {
+    pub fn new() -> Self {
+        let mut kinds = Kinds {
+            names: vec![],
+            ids: HashMap::new(),
+        };
+
+        for name in RESERVED {
+            kinds.intern(name);
+        }
+
+        kinds
+    }
+
+    pub fn intern(&mut self, name: &'a str) -> TokenKind {
+        if let Some(kind) = self.ids.get(name) {
+            return *kind;
+        }
+
+        let kind = TokenKind(u16::try_from(self.names.len()).expect("too many token types"));
+        self.names.push(name);
+        self.ids.insert(name, kind);
+
+        kind
+    }
+
+    pub fn get(&self, name: &str) -> Option<TokenKind> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, kind: TokenKind) -> Option<&'a str> {
+        self.names.get(kind.0 as usize).copied()
+    }
+}
@@ -0,0 +1,1044 @@
+pub mod ast;
+pub use ast::{ASTGen, AstError, Child, Node};
+
+pub mod comment;
+pub use comment::Comment;
+use comment::CommentRule;
+
+pub mod earley;
+pub use earley::{Forest, ForestChild, ForestNode, Packed};
+
+pub mod exaust;
+pub use exaust::{Exaust, ExaustError};
+
+pub mod kind;
+use kind::Kinds;
+pub use kind::{Span, TokenKind};
+
+pub mod lr;
+pub use lr::{LrAction, LrState, LrTable, LR_END};
+
+pub mod mode;
+use mode::Mode;
+pub use mode::{ModeAction, DEFAULT_MODE};
+
+pub mod offside;
+pub use offside::Offside;
+use offside::{DEDENT, INDENT};
+
+pub mod precedence;
+pub use precedence::{Assoc, Precedence};
+
+pub mod stream;
+pub use stream::{StreamError, TokenStream};
+
+pub mod value;
+pub use value::{Converter, TokenValue};
+
+use regex::Regex;
+use regex_automata::{meta, Anchored, Input, PatternID, PatternSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::BufRead;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A token found by a searcher.
+///
+/// `line`, `start` and `end` are 1-based and count chars (Unicode scalar
+/// values), not bytes or UTF-16 units. `end` is the column just after the
+/// last char, in the line where the token ends. `offset` is the byte offset
+/// of `content` in the tokenized source.
+#[derive(Debug, Clone, Serialize)]
+pub struct Token<'a> {
+    pub ty: &'a str,
+    /// The interned `ty`, see `TFLA::kind`.
+    pub kind: TokenKind,
+    pub content: &'a str,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub offset: usize,
+    /// The named capture groups of the searcher that matched, the groups
+    /// that didn't match are left out.
+    pub groups: Vec<(Arc<str>, &'a str)>,
+    /// The value made by the converter of the searcher, if it has one.
+    pub value: Option<TokenValue>,
+}
+
+impl<'a> Token<'a> {
+    pub fn new(
+        ty: &'a str,
+        kind: TokenKind,
+        content: &'a str,
+        line: usize,
+        start: usize,
+        end: usize,
+        offset: usize,
+    ) -> Self {
+        Token {
+            ty,
+            kind,
+            content,
+            line,
+            start,
+            end,
+            offset,
+            groups: vec![],
+            value: None,
+        }
+    }
+
+    /// The content matched by the capture group named `name`, like
+    /// `(?P<value>...)`.
+    pub fn group(&self, name: &str) -> Option<&'a str> {
+        self.groups
+            .iter()
+            .find(|(n, _)| &**n == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// The byte range of the token in the tokenized source.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.content.len()
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + self.content.len(),
+            line: self.line,
+            column: self.start,
+        }
+    }
+
+    /// Copies the token so it don't borrow the source or the searchers.
+    pub fn into_owned(self) -> OwnedToken {
+        OwnedToken {
+            ty: self.ty.to_string(),
+            kind: self.kind,
+            content: self.content.to_string(),
+            line: self.line,
+            start: self.start,
+            end: self.end,
+            offset: self.offset,
+            groups: self
+                .groups
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            value: self.value,
+        }
+    }
+}
+
+/// A [`Token`] that owns its type and content, so it can be kept after the
+/// source and the TFLA are dropped, or sent to another thread.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedToken {
+    pub ty: String,
+    pub kind: TokenKind,
+    pub content: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub offset: usize,
+    pub groups: Vec<(String, String)>,
+    pub value: Option<TokenValue>,
+}
+
+impl<'a> From<Token<'a>> for OwnedToken {
+    fn from(token: Token<'a>) -> Self {
+        token.into_owned()
+    }
+}
+
+impl OwnedToken {
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + self.content.len(),
+            line: self.line,
+            column: self.start,
+        }
+    }
+
+    /// The content matched by the capture group named `name`.
+    pub fn group(&self, name: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| &value[..])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TokenizeErrorKind {
+    /// No searcher matches the code.
+    NoMatch,
+    /// A block comment is never closed, the error is where it opened.
+    UnterminatedComment,
+    /// The converter of the searcher can't convert the token, like an
+    /// integer that overflows.
+    InvalidValue(String),
+    /// A line is less indented than the last one, but not as indented as any
+    /// open block, see `TFLA::offside`.
+    InconsistentIndentation,
+}
+
+/// The error returned when no searcher matches the code being tokenized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenizeError {
+    pub kind: TokenizeErrorKind,
+    pub line: usize,
+    pub column: usize,
+    /// Byte offset of the unmatched code in the source.
+    pub offset: usize,
+    /// The unmatched code, up to the end of its line.
+    pub snippet: String,
+}
+
+impl TokenizeError {
+    const SNIPPET_LEN: usize = 32;
+
+    fn new(kind: TokenizeErrorKind, line: usize, column: usize, offset: usize, code: &str) -> Self {
+        let snippet = code
+            .chars()
+            .take_while(|c| *c != '\r' && *c != '\n')
+            .take(TokenizeError::SNIPPET_LEN)
+            .collect();
+
+        TokenizeError {
+            kind,
+            line,
+            column,
+            offset,
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            TokenizeErrorKind::NoMatch => write!(
+                f,
+                "Any token match found at \"{}\". |{} row {}|",
+                self.snippet, self.line, self.column
+            ),
+            TokenizeErrorKind::UnterminatedComment => write!(
+                f,
+                "Unterminated comment opened at \"{}\". |{} row {}|",
+                self.snippet, self.line, self.column
+            ),
+            TokenizeErrorKind::InconsistentIndentation => write!(
+                f,
+                "The indentation don't match any outer block at \"{}\". |{} row {}|",
+                self.snippet, self.line, self.column
+            ),
+            TokenizeErrorKind::InvalidValue(e) => {
+                write!(
+                    f,
+                    "{} at \"{}\". |{} row {}|",
+                    e, self.snippet, self.line, self.column
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+/// The type of the tokens that hold code not matched by any searcher, when
+/// `recover_errors` is enabled.
+pub const ERROR: &str = "ERROR";
+
+/// How the searcher of a token is chosen when more than one matches.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MatchPolicy {
+    /// The first searcher in declaration order.
+    #[default]
+    First,
+    /// The searcher with the longest match, ties go to the first declared.
+    Longest,
+    /// The searcher with the highest entry in `TFLA::priorities`, ties go to
+    /// the longest match and then to the first declared.
+    Priority,
+}
+
+/// The position, the modes and the indentation while tokenizing some code,
+/// kept apart from the TFLA so a tokenizer can be used many times at once.
+#[derive(Debug, Clone)]
+struct TokenizeState {
+    line: usize,
+    row: usize,
+    /// The indexes in `TFLA::modes` of the modes pushed, the last one is the
+    /// active mode.
+    modes: Vec<usize>,
+
+    /// The code is at the start of a line, so its indentation is checked.
+    line_start: bool,
+    /// The widths of the open indentation levels, see `TFLA::offside`.
+    indents: Vec<usize>,
+    /// The INDENT, DEDENT and ERROR tokens found but not yielded yet.
+    pending: VecDeque<&'static str>,
+
+    /// The errors recovered, see `TFLA::recover_errors`.
+    errors: Vec<TokenizeError>,
+}
+
+impl TokenizeState {
+    fn new() -> Self {
+        TokenizeState {
+            line: 1,
+            row: 1,
+            modes: vec![0],
+            line_start: true,
+            indents: vec![0],
+            pending: VecDeque::new(),
+            errors: vec![],
+        }
+    }
+
+    /// Creates a token at the current position and moves past it.
+    fn token<'s>(
+        &mut self,
+        ty: &'s str,
+        kind: TokenKind,
+        content: &'s str,
+        offset: usize,
+    ) -> Token<'s> {
+        let (line, start) = (self.line, self.row);
+
+        self.advance(content);
+
+        Token::new(ty, kind, content, line, start, self.row, offset)
+    }
+
+    fn mode(&self) -> usize {
+        self.modes.last().copied().unwrap_or(0)
+    }
+
+    /// Moves the current line and column past the code.
+    fn advance(&mut self, code: &str) {
+        for c in code.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.row = 1;
+            } else {
+                self.row += 1;
+            }
+
+            self.line_start = c == '\n';
+        }
+    }
+}
+
+/// What `TFLA::search` found at the start of some code.
+#[derive(Debug, Copy, Clone)]
+struct Found<'a> {
+    ty: &'a str,
+    kind: TokenKind,
+    end: usize,
+    /// The index of the searcher in `TFLA::searchers`, `None` for comments.
+    searcher: Option<usize>,
+    /// A block comment that goes up to the end of the code without a close.
+    unterminated: bool,
+}
+
+/// A searcher with named capture groups, compiled alone to find its groups
+/// once it matches.
+struct Groups {
+    regex: meta::Regex,
+    names: Vec<(usize, Arc<str>)>,
+}
+
+impl Groups {
+    fn new(regex: &str) -> Option<Self> {
+        let regex = meta::Regex::new(regex).ok()?;
+        let names: Vec<(usize, Arc<str>)> = regex
+            .group_info()
+            .pattern_names(PatternID::ZERO)
+            .enumerate()
+            .filter_map(|(i, name)| Some((i, Arc::from(name?))))
+            .collect();
+
+        if names.is_empty() {
+            return None;
+        }
+
+        Some(Groups { regex, names })
+    }
+
+    /// The groups of the token that ends at `end`, searched in the same code
+    /// than the searcher, so the look-arounds like `\b` or `$` see the code
+    /// after the token.
+    fn find<'s>(&self, code: &'s str, end: usize) -> Vec<(Arc<str>, &'s str)> {
+        let mut caps = self.regex.create_captures();
+        let input = Input::new(code).range(..end).anchored(Anchored::Yes);
+        self.regex.search_captures(&input, &mut caps);
+
+        self.names
+            .iter()
+            .filter_map(|(i, name)| Some((name.clone(), &code[caps.get_group(*i)?.range()])))
+            .collect()
+    }
+}
+
+/// Why `TFLA::try_new` can't use a searcher.
+#[derive(Debug, Clone)]
+pub enum SearcherError {
+    Regex(regex::Error),
+    /// The name is ERROR, INDENT or DEDENT, the token types made by TFLA, so
+    /// its tokens would look like the ones made by TFLA.
+    Reserved,
+}
+
+impl fmt::Display for SearcherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearcherError::Regex(e) => write!(f, "Invalid regex: {}", e),
+            SearcherError::Reserved => write!(f, "The name is reserved by TFLA"),
+        }
+    }
+}
+
+impl std::error::Error for SearcherError {}
+
+pub struct TFLA<'a> {
+    searchers: Vec<(&'a str, &'a str)>,
+    kinds: Kinds<'a>,
+    /// The kind of each searcher.
+    searcher_kinds: Vec<TokenKind>,
+    /// The searchers of each mode compiled in a single automaton, the first
+    /// mode is the default one.
+    modes: Vec<Mode<'a>>,
+    actions: HashMap<&'a str, ModeAction<'a>>,
+    converters: HashMap<&'a str, Converter>,
+    /// The keywords of each searcher, see `add_keywords`.
+    keywords: HashMap<&'a str, HashMap<&'a str, TokenKind>>,
+    /// The capture groups of each searcher, if it has named groups.
+    groups: Vec<Option<Groups>>,
+    comments: Vec<CommentRule<'a>>,
+
+    pub assemblers: Vec<(&'a str, Vec<&'a str>)>,
+    pub symbols: Vec<(&'a str, &'a str)>,
+    /// The precedence of the operators of the assemblers, from the loosest
+    /// to the tightest, see `ASTGen::add_precedence`.
+    pub precedences: Vec<Precedence<'a>>,
+    pub ignore_spaces: bool,
+    /// Emit the comments as tokens instead of dropping them.
+    pub keep_comments: bool,
+    /// Instead of stopping at the first code not matched by any searcher,
+    /// push it as an `ERROR` token and keep tokenizing.
+    pub recover_errors: bool,
+    pub match_policy: MatchPolicy,
+    /// Emit `INDENT` and `DEDENT` tokens following the indentation of the
+    /// lines.
+    pub offside: Option<Offside>,
+    /// The priority of each searcher used by `MatchPolicy::Priority`, the
+    /// searchers not listed have priority 0.
+    pub priorities: HashMap<&'a str, i32>,
+    /// The LALR(1) table of the assemblers, built by `tfla-cc`, see
+    /// `assemble_lr`.
+    pub lr_table: Option<LrTable<'a>>,
+    /// The assemblers are ambiguous, so they're assembled with
+    /// `assemble_forest`, set by the `{parser} : earley` directive.
+    pub earley: bool,
+
+    /// The tokens found by the last call to `tokenize`.
+    pub tokens: Vec<Token<'a>>,
+    /// The errors recovered while tokenizing, see `recover_errors`.
+    pub errors: Vec<TokenizeError>,
+}
+
+impl<'a> TFLA<'a> {
+    pub fn new(
+        searchers: Vec<(&'a str, &'a str)>,
+        assemblers: Vec<(&'a str, Vec<&'a str>)>,
+        ignore_spaces: bool,
+    ) -> Self {
+        match TFLA::try_new(searchers, assemblers, ignore_spaces) {
+            Ok(tfla) => tfla,
+            Err((name, SearcherError::Regex(e))) => {
+                panic!("Invalid regex in Searcher [{}]: {}", name, e)
+            }
+            Err((name, SearcherError::Reserved)) => {
+                panic!("The Searcher [{}] have a name reserved by TFLA", name)
+            }
+        }
+    }
+
+    /// Like `new`, but returns the name of the searcher with an invalid regex
+    /// or a reserved name instead of panicking.
+    pub fn try_new(
+        searchers: Vec<(&'a str, &'a str)>,
+        assemblers: Vec<(&'a str, Vec<&'a str>)>,
+        ignore_spaces: bool,
+    ) -> Result<Self, (&'a str, SearcherError)> {
+        for (a, b) in &searchers {
+            if kind::RESERVED.contains(a) {
+                return Err((*a, SearcherError::Reserved));
+            }
+
+            Regex::new(b).map_err(|e| (*a, SearcherError::Regex(e)))?;
+        }
+
+        let mut kinds = Kinds::new();
+        let searcher_kinds = searchers
+            .iter()
+            .map(|(name, _)| kinds.intern(name))
+            .collect();
+
+        Ok(TFLA {
+            kinds,
+            searcher_kinds,
+            modes: vec![Mode::new(
+                DEFAULT_MODE,
+                (0..searchers.len()).collect(),
+                &searchers,
+            )],
+            actions: HashMap::new(),
+            converters: HashMap::new(),
+            keywords: HashMap::new(),
+            groups: searchers
+                .iter()
+                .map(|(_, regex)| Groups::new(regex))
+                .collect(),
+            searchers,
+            comments: vec![],
+            assemblers,
+            symbols: vec![],
+            precedences: vec![],
+            ignore_spaces,
+            keep_comments: false,
+            recover_errors: false,
+            match_policy: MatchPolicy::First,
+            offside: None,
+            priorities: HashMap::new(),
+            lr_table: None,
+            earley: false,
+            tokens: vec![],
+            errors: vec![],
+        })
+    }
+
+    /// Applies the match found by `search` at the start of the code, returning
+    /// the token found, if it isn't skipped, and how many bytes were used.
+    fn found_tokens<'s>(
+        &self,
+        state: &mut TokenizeState,
+        code: &'s str,
+        offset: usize,
+        found: Option<Found<'a>>,
+    ) -> Result<(Option<Token<'s>>, usize), TokenizeError>
+    where
+        'a: 's,
+    {
+        let error_kind = match found {
+            Some(Found {
+                ty,
+                kind,
+                end,
+                searcher,
+                unterminated: false,
+            }) => {
+                let content: &'s str = &code[..end];
+                let comment = searcher.is_none();
+                let (ty, kind) = self.keyword(comment, ty, content).unwrap_or((ty, kind));
+
+                match self.convert(comment, ty, content) {
+                    Ok(value) => {
+                        let mut tk = state.token(ty, kind, content, offset);
+                        tk.value = value;
+                        if let Some(Some(groups)) = searcher.map(|i| &self.groups[i]) {
+                            tk.groups = groups.find(code, end);
+                        }
+
+                        if let Some(action) = self.actions.get(ty) {
+                            self.apply(state, *action);
+                        }
+
+                        if comment && !self.keep_comments || ty == "SPACE" && self.ignore_spaces {
+                            return Ok((None, end));
+                        }
+
+                        return Ok((Some(tk), end));
+                    }
+                    Err(e) => TokenizeErrorKind::InvalidValue(e),
+                }
+            }
+            Some(_) => TokenizeErrorKind::UnterminatedComment,
+            None => TokenizeErrorKind::NoMatch,
+        };
+
+        let error = TokenizeError::new(error_kind, state.line, state.row, offset, code);
+
+        if !self.recover_errors {
+            return Err(error);
+        }
+
+        let end = match found {
+            Some(found) => found.end,
+            None => self.recover_end(state, code),
+        };
+        let tk = state.token(ERROR, TokenKind::ERROR, &code[..end], offset);
+
+        state.errors.push(error);
+
+        Ok((Some(tk), end))
+    }
+
+    /// Makes the tokens of the searcher with one of the keywords as content
+    /// have the keyword as type, like an `Entity` token `if` becomes an `if`
+    /// token. The actions and converters of the keyword are used instead of
+    /// the ones of the searcher.
+    pub fn add_keywords(&mut self, searcher: &'a str, keywords: &[&'a str]) {
+        let table = self.keywords.entry(searcher).or_default();
+
+        for keyword in keywords {
+            table.insert(keyword, self.kinds.intern(keyword));
+        }
+    }
+
+    fn keyword(&self, comment: bool, ty: &str, content: &str) -> Option<(&'a str, TokenKind)> {
+        if comment {
+            return None;
+        }
+
+        self.keywords
+            .get(ty)?
+            .get_key_value(content)
+            .map(|(keyword, kind)| (*keyword, *kind))
+    }
+
+    /// The kind of the token type named `name`, if some searcher, keyword or
+    /// comment has this name.
+    pub fn kind(&self, name: &str) -> Option<TokenKind> {
+        self.kinds.get(name)
+    }
+
+    pub fn kind_name(&self, kind: TokenKind) -> Option<&'a str> {
+        self.kinds.name(kind)
+    }
+
+    /// Converts the content of a token with the converter of its searcher.
+    fn convert(
+        &self,
+        comment: bool,
+        ty: &str,
+        content: &str,
+    ) -> Result<Option<TokenValue>, String> {
+        match self.converters.get(ty) {
+            Some(converter) if !comment => converter.convert(content).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Gives the tokens of the searcher a value made by the converter. When
+    /// the conversion fails, it's a lexing error where the token starts.
+    pub fn add_converter(&mut self, searcher: &'a str, converter: Converter) {
+        self.converters.insert(searcher, converter);
+    }
+
+    /// The INDENT, DEDENT or ERROR token due before the code, when `offside`
+    /// is enabled and the code starts a line.
+    fn offside_token<'s>(
+        &self,
+        state: &mut TokenizeState,
+        code: &'s str,
+        offset: usize,
+    ) -> Result<Option<Token<'s>>, TokenizeError> {
+        let offside = match &self.offside {
+            Some(offside) => offside,
+            None => return Ok(None),
+        };
+
+        if state.line_start {
+            state.line_start = false;
+
+            let rest = code.trim_start_matches([' ', '\t']);
+            let blank = rest.is_empty()
+                || rest.starts_with(['\r', '\n'])
+                || state.mode() == 0 && self.comments.iter().any(|rule| rule.find(rest).is_some());
+
+            if !blank {
+                let width = offside.width(&code[..code.len() - rest.len()]);
+
+                if width > state.indents[state.indents.len() - 1] {
+                    state.indents.push(width);
+                    state.pending.push_back(INDENT);
+                }
+
+                while width < state.indents[state.indents.len() - 1] {
+                    state.indents.pop();
+                    state.pending.push_back(DEDENT);
+                }
+
+                if width > state.indents[state.indents.len() - 1] {
+                    let error = TokenizeError::new(
+                        TokenizeErrorKind::InconsistentIndentation,
+                        state.line,
+                        state.row,
+                        offset,
+                        code,
+                    );
+
+                    if !self.recover_errors {
+                        return Err(error);
+                    }
+
+                    state.errors.push(error);
+                    state.indents.push(width);
+                    state.pending.push_back(ERROR);
+                }
+            }
+        }
+
+        Ok(state
+            .pending
+            .pop_front()
+            .map(|ty| state.token(ty, self.kinds.get(ty).unwrap(), &code[..0], offset)))
+    }
+
+    /// A DEDENT token for each block still open at the end of the code.
+    fn offside_end<'s>(
+        &self,
+        state: &mut TokenizeState,
+        code: &'s str,
+        offset: usize,
+    ) -> Option<Token<'s>> {
+        if self.offside.is_none() || state.indents.len() < 2 {
+            return None;
+        }
+
+        state.indents.pop();
+        Some(state.token(DEDENT, TokenKind::DEDENT, &code[code.len()..], offset))
+    }
+
+    /// The searchers names and regexes, in declaration order.
+    pub fn searchers(&self) -> &[(&'a str, &'a str)] {
+        &self.searchers
+    }
+
+    /// Adds a comment rule, the comments are searched in the order they were
+    /// added and before any searcher.
+    pub fn add_comment(&mut self, comment: Comment<'a>) -> Result<(), regex::Error> {
+        let kind = self.kinds.intern(comment.name);
+        self.comments.push(CommentRule::new(comment, kind)?);
+
+        Ok(())
+    }
+
+    /// The comment rules, in the order they were added.
+    pub fn comments(&self) -> impl Iterator<Item = &Comment<'a>> {
+        self.comments.iter().map(|rule| &rule.comment)
+    }
+
+    /// Adds a mode with the searchers listed, replacing the mode with the
+    /// same name. Returns the first searcher that don't exists.
+    ///
+    /// Adding the `DEFAULT_MODE` limits the searchers tried when no other
+    /// mode was entered.
+    pub fn add_mode(&mut self, name: &'a str, searchers: &[&'a str]) -> Result<(), &'a str> {
+        let indexes = searchers
+            .iter()
+            .map(|s| {
+                self.searchers
+                    .iter()
+                    .position(|(name, _)| name == s)
+                    .ok_or(*s)
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let mode = Mode::new(name, indexes, &self.searchers);
+        match self.modes.iter().position(|m| m.name == name) {
+            Some(i) => self.modes[i] = mode,
+            None => self.modes.push(mode),
+        }
+
+        Ok(())
+    }
+
+    /// The modes names, the first one is the `DEFAULT_MODE`.
+    pub fn modes(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.modes.iter().map(|m| m.name)
+    }
+
+    /// Changes the mode each time the searcher matches.
+    ///
+    /// Entering a mode that wasn't added goes to the default mode.
+    pub fn add_action(&mut self, searcher: &'a str, action: ModeAction<'a>) {
+        self.actions.insert(searcher, action);
+    }
+
+    fn apply(&self, state: &mut TokenizeState, action: ModeAction<'a>) {
+        let index = |name: &str| self.modes.iter().position(|m| m.name == name).unwrap_or(0);
+
+        match action {
+            ModeAction::Push(name) => state.modes.push(index(name)),
+            ModeAction::Pop => {
+                if state.modes.len() > 1 {
+                    state.modes.pop();
+                }
+            }
+            ModeAction::Switch(name) => {
+                state.modes.pop();
+                state.modes.push(index(name));
+            }
+        }
+    }
+
+    /// Finds the comment or the searcher of the active mode that matches at
+    /// the start of the code.
+    ///
+    /// The comments are only searched in the default mode, so they can't
+    /// start inside a string, for example.
+    fn search(&self, state: &TokenizeState, code: &str) -> Option<Found<'a>> {
+        let mode = &self.modes[state.mode()];
+        let comments = if state.mode() == 0 {
+            &self.comments[..]
+        } else {
+            &[]
+        };
+
+        for rule in comments {
+            if let Some((end, closed)) = rule.find(code) {
+                return Some(Found {
+                    ty: rule.comment.name,
+                    kind: rule.kind,
+                    end,
+                    searcher: None,
+                    unterminated: !closed,
+                });
+            }
+        }
+
+        self.search_searchers(mode, code).map(|(i, end)| Found {
+            ty: self.searchers[i].0,
+            kind: self.searcher_kinds[i],
+            end,
+            searcher: Some(i),
+            unterminated: false,
+        })
+    }
+
+    /// Finds the searcher that matches the code following `match_policy`,
+    /// returning its index and the end of the match.
+    ///
+    /// Searchers only match at the start of the code, as if their regex
+    /// started with `^`. An empty match, like `^\d*` before a letter, isn't
+    /// a match, since the tokenizer would never move.
+    fn search_searchers(&self, mode: &Mode<'a>, code: &str) -> Option<(usize, usize)> {
+        let input = Input::new(code).anchored(Anchored::Yes);
+        let index = |pattern: usize| mode.searchers[pattern];
+
+        // With leftmost-first semantics the automaton already prefers the
+        // first declared searcher, so a single search is enough, unless the
+        // searcher found matched nothing.
+        if self.match_policy == MatchPolicy::First {
+            match mode.automaton.search(&input) {
+                Some(m) if m.is_empty() => (),
+                found => return found.map(|m| (index(m.pattern().as_usize()), m.end())),
+            }
+        }
+
+        let mut matched = PatternSet::new(mode.searchers.len());
+        mode.automaton_all
+            .which_overlapping_matches(&input, &mut matched);
+
+        let mut matches = matched.iter().filter_map(|pattern| {
+            let input = Input::new(code).anchored(Anchored::Pattern(pattern));
+
+            mode.automaton
+                .search(&input)
+                .filter(|m| !m.is_empty())
+                .map(|m| (index(pattern.as_usize()), m.end()))
+        });
+
+        match self.match_policy {
+            MatchPolicy::First => matches.next(),
+            MatchPolicy::Longest => matches.fold(None, |best, m| match best {
+                Some((_, end)) if end >= m.1 => best,
+                _ => Some(m),
+            }),
+            MatchPolicy::Priority => {
+                let priority = |i: usize| {
+                    let name = self.searchers[i].0;
+                    self.priorities.get(name).copied().unwrap_or(0)
+                };
+
+                matches.fold(None, |best, m| match best {
+                    Some((i, end)) if (priority(i), end) >= (priority(m.0), m.1) => best,
+                    _ => Some(m),
+                })
+            }
+        }
+    }
+
+    /// Finds where some searcher or comment matches again after unmatched
+    /// code.
+    fn recover_end(&self, state: &TokenizeState, code: &str) -> usize {
+        for (i, _) in code.char_indices().skip(1) {
+            let rest = &code[i..];
+
+            if self
+                .search_searchers(&self.modes[state.mode()], rest)
+                .is_some()
+                || state.mode() == 0 && self.comments.iter().any(|rule| rule.find(rest).is_some())
+            {
+                return i;
+            }
+        }
+
+        code.len()
+    }
+
+    /// Tokenizes the source lazily, each token is searched when the
+    /// iterator asks for it.
+    ///
+    /// The tokenizer isn't changed, so it can tokenize many sources at once,
+    /// even from different threads.
+    pub fn tokens<'t, 's>(&'t self, source: &'s str) -> Tokens<'t, 's>
+    where
+        'a: 's,
+    {
+        Tokens {
+            tfla: self,
+            source,
+            offset: 0,
+            state: TokenizeState::new(),
+            failed: false,
+        }
+    }
+
+    /// Tokenizes the source, returning the tokens found.
+    ///
+    /// If some part of the source don't match any searcher, the error tells
+    /// where, and `tokens` keeps the tokens found before it. With
+    /// `recover_errors` the tokenization never fails, look at `errors` instead.
+    /// Each call starts from the first line again, replacing `tokens` and
+    /// `errors`.
+    pub fn tokenize(&mut self, source: &'a str) -> Result<Vec<Token<'a>>, TokenizeError> {
+        let mut tokens = vec![];
+        let mut res = Ok(());
+
+        let mut iter = self.tokens(source);
+
+        for tk in &mut iter {
+            match tk {
+                Ok(tk) => tokens.push(tk),
+                Err(e) => res = Err(e),
+            }
+        }
+
+        self.errors = iter.state.errors;
+        self.tokens = tokens;
+
+        res.map(|_| self.tokens.clone())
+    }
+
+    /// Tokenizes the code read from `reader` as it's needed, yielding owned
+    /// tokens, so the whole source is never held in memory.
+    ///
+    /// The code is read line by line, and a token is only yielded once its
+    /// match ends before the code read so far, so tokens can span chunks.
+    pub fn stream<R: BufRead>(&self, reader: R) -> TokenStream<'_, 'a, R> {
+        TokenStream::new(self, reader)
+    }
+
+    /// Assembles the tokens found by `tokenize` into an AST, starting from
+    /// the assembler named `root`.
+    pub fn assemble(&self, root: &'a str) -> Result<Node<'a>, AstError> {
+        let mut ast_gen = ASTGen::new(self.assemblers.clone(), self.symbols.clone());
+
+        for precedence in &self.precedences {
+            ast_gen.add_precedence(precedence);
+        }
+
+        ast_gen.generate(&self.tokens, root)
+    }
+
+    /// Assembles the tokens found by `tokenize` with the `lr_table`, starting
+    /// from the first assembler.
+    pub fn assemble_lr(&self) -> Result<Node<'a>, AstError> {
+        let table = self.lr_table.as_ref().ok_or(AstError::NoLrTable)?;
+        let ast_gen = ASTGen::new(self.assemblers.clone(), self.symbols.clone());
+
+        ast_gen.generate_lr(&self.tokens, table)
+    }
+
+    /// Assembles the tokens found by `tokenize` with an Earley parser,
+    /// returning every tree of the assembler named `root`.
+    pub fn assemble_forest(&self, root: &'a str) -> Result<Forest<'a>, AstError> {
+        let ast_gen = ASTGen::new(self.assemblers.clone(), self.symbols.clone());
+
+        ast_gen.generate_forest(&self.tokens, root)
+    }
+}
+
+/// An iterator over the tokens of a source, created by [`TFLA::tokens`].
+pub struct Tokens<'t, 's> {
+    tfla: &'t TFLA<'s>,
+    source: &'s str,
+    /// Where the code not tokenized yet starts in `source`.
+    offset: usize,
+
+    state: TokenizeState,
+    failed: bool,
+}
+
+impl<'t, 's> Tokens<'t, 's> {
+    /// The errors recovered so far, see `TFLA::recover_errors`.
+    pub fn errors(&self) -> &[TokenizeError] {
+        &self.state.errors
+    }
+}
+
+impl<'t, 's> Iterator for Tokens<'t, 's> {
+    type Item = Result<Token<'s>, TokenizeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            let code = &self.source[self.offset..];
+
+            if code.is_empty() {
+                return self
+                    .tfla
+                    .offside_end(&mut self.state, code, self.offset)
+                    .map(Ok);
+            }
+
+            match self.tfla.offside_token(&mut self.state, code, self.offset) {
+                Ok(Some(tk)) => return Some(Ok(tk)),
+                Ok(None) => (),
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+
+            let found = self.tfla.search(&self.state, code);
+
+            match self
+                .tfla
+                .found_tokens(&mut self.state, code, self.offset, found)
+            {
+                Ok((tk, end)) => {
+                    self.offset += end;
+
+                    if tk.is_some() {
+                        return tk.map(Ok);
+                    }
+                }
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,169 @@
+use crate::ast::{ASTGen, Argument, AstError, Child, Node};
+use crate::Token;
+
+/// The terminal of the end of the tokens in an LR table.
+pub const LR_END: &str = "$";
+
+/// What the LR parser does when a state sees a terminal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LrAction {
+    /// Consumes the token and goes to the state.
+    Shift(usize),
+    /// Replaces the arguments of the assembler with that index by a node.
+    Reduce(usize),
+    /// The root assembler matched all the tokens.
+    Accept,
+}
+
+/// A state of an [`LrTable`], the terminals are written like the arguments
+/// of the assemblers, as `se-Number` or `li-"+"`, or [`LR_END`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LrState<'a> {
+    pub actions: Vec<(&'a str, LrAction)>,
+    /// The state to go after reducing each assembler.
+    pub gotos: Vec<(&'a str, usize)>,
+}
+
+impl<'a> LrState<'a> {
+    /// Parses the entries of an `lr` line of an exaust, like `se-Number=s3`,
+    /// `li-"+"=r2`, `$=a` or `as-expr=g4`.
+    pub fn parse(entries: &'a str) -> Option<Self> {
+        let mut state = LrState::default();
+
+        for entry in entries.split(' ').filter(|e| !e.is_empty()) {
+            let (symbol, action) = entry.rsplit_once('=')?;
+            let (kind, target) = action.split_at_checked(1)?;
+
+            match (kind, target.parse::<usize>()) {
+                ("s", Ok(s)) => state.actions.push((symbol, LrAction::Shift(s))),
+                ("r", Ok(r)) => state.actions.push((symbol, LrAction::Reduce(r))),
+                ("a", _) if target.is_empty() => state.actions.push((symbol, LrAction::Accept)),
+                ("g", Ok(g)) => state.gotos.push((symbol.strip_prefix("as-")?, g)),
+                _ => return None,
+            }
+        }
+
+        Some(state)
+    }
+
+    fn goto(&self, assembler: &str) -> Option<usize> {
+        self.gotos
+            .iter()
+            .find(|(name, _)| *name == assembler)
+            .map(|(_, state)| *state)
+    }
+}
+
+/// The LALR(1) table built by `tfla-cc` for the first assembler, see
+/// `TFLA::assemble_lr`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LrTable<'a> {
+    pub states: Vec<LrState<'a>>,
+}
+
+impl<'a> ASTGen<'a> {
+    /// Assembles the tokens with a table-driven LR parser, without trying
+    /// alternatives, the table already knows which one matches.
+    pub fn generate_lr(
+        &self,
+        tokens: &[Token<'a>],
+        table: &LrTable<'a>,
+    ) -> Result<Node<'a>, AstError> {
+        // The state, the node or token shifted, and the index of its first
+        // token.
+        let mut stack: Vec<(usize, Option<Child<'a>>, usize)> = vec![(0, None, 0)];
+        let mut pos = 0;
+
+        loop {
+            let state = &table.states[stack[stack.len() - 1].0];
+
+            let (terminal, action) = match self.lr_action(state, tokens, pos) {
+                Some(found) => found,
+                None => {
+                    return Err(AstError::Unexpected {
+                        index: pos,
+                        found: tokens.get(pos).map(|t| t.content.to_string()),
+                        expected: state
+                            .actions
+                            .iter()
+                            .map(|(terminal, _)| match *terminal {
+                                LR_END => "end of tokens".to_string(),
+                                terminal => Argument::from(terminal).to_string(),
+                            })
+                            .collect(),
+                    })
+                }
+            };
+
+            match action {
+                LrAction::Shift(next) => {
+                    // The symbols that match a position, like :eof:, don't
+                    // consume a token.
+                    if matches!(terminal, "sy-eof" | "sy-eol") {
+                        stack.push((next, None, pos));
+                    } else {
+                        stack.push((next, Some(Child::Token(tokens[pos].clone())), pos));
+                        pos += 1;
+                    }
+                }
+                LrAction::Reduce(index) => {
+                    let (rule, arguments) = &self.assemblers[index];
+                    let len = arguments.iter().filter(|a| **a != Argument::Empty).count();
+                    // A table made for other assemblers could pop the
+                    // state at the bottom of the stack.
+                    let at = match stack.len().checked_sub(len) {
+                        Some(at) if at > 0 => at,
+                        _ => return Err(AstError::NoLrTable),
+                    };
+                    let popped = stack.split_off(at);
+
+                    let start = popped.first().map_or(pos, |(_, _, start)| *start);
+                    let children = popped
+                        .into_iter()
+                        .filter_map(|(_, child, _)| child)
+                        .collect();
+                    let node = Node::new(rule, children, start, pos);
+
+                    let next = table.states[stack[stack.len() - 1].0]
+                        .goto(rule)
+                        .ok_or(AstError::NoLrTable)?;
+                    stack.push((next, Some(Child::Node(node)), start));
+                }
+                LrAction::Accept => {
+                    return match stack.pop() {
+                        Some((_, Some(Child::Node(node)), _)) => Ok(node),
+                        _ => Err(AstError::NoLrTable),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The action of the terminal that matches at `pos`. The literals are
+    /// tried before the symbols and the symbols before the searchers, since a
+    /// token of a searcher can also be a literal.
+    fn lr_action(
+        &self,
+        state: &LrState<'a>,
+        tokens: &[Token<'a>],
+        pos: usize,
+    ) -> Option<(&'a str, LrAction)> {
+        let token = tokens.get(pos);
+
+        let matches = |terminal: &str| match (Argument::from(terminal), token) {
+            (Argument::Symbol("eof"), _) => token.is_none(),
+            (Argument::Symbol("eol"), None) => true,
+            (Argument::Symbol("eol"), Some(token)) => self.symbol_match("nwl", token.content),
+            (argument, Some(token)) => terminal != LR_END && self.token_match(&argument, token),
+            (_, None) => terminal == LR_END,
+        };
+
+        ["li-", "sy-", "se-", ""].iter().find_map(|prefix| {
+            state
+                .actions
+                .iter()
+                .find(|(terminal, _)| terminal.starts_with(prefix) && matches(terminal))
+                .copied()
+        })
+    }
+}
@@ -1,134 +1,174 @@
-fn main() {
-    let mut searchers: Vec<(&str, &str)> = vec![];
-
-    searchers.push(("SPACE", r"^\s"));
-    searchers.push(("NEW_LINE", r"^(\r)?\n"));
-    searchers.push(("Entity", r"^\w+"));
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::exit;
 
-    let mut tokenizer: TFLA = TFLA::new(searchers, vec![], false);
+use tfla::{Exaust, TFLA};
 
-    tokenizer.tokenize("test\ntes t\nte st\nt est");
+fn main() {
+    let args: Vec<String> = env::args().collect();
 
-    for token in tokenizer.tokens {
-        print!("{}", token.content);
+    if args.get(1).map(String::as_str) == Some("tokenize") {
+        tokenize(&args[2..]);
+        return;
     }
-}
 
-use regex::Regex;
+    if args.len() == 3 {
+        run_exaust(&args[1], &args[2]);
+        return;
+    }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Token<'a> {
-    pub ty: &'a str,
-    pub content: &'a str,
-    pub line: usize,
-    pub start: usize,
-    pub end: usize,
-}
+    let searchers: Vec<(&str, &str)> = vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("Entity", r"^\w+"),
+    ];
+
+    let assemblers: Vec<(&str, Vec<&str>)> = vec![
+        ("lines", vec!["as-line", "sy-nwl", "as-lines"]),
+        ("lines", vec!["as-line", "sy-eof"]),
+        ("line", vec!["se-Entity", "as-line"]),
+        ("line", vec!["se-Entity"]),
+    ];
+
+    let mut tokenizer: TFLA = TFLA::new(searchers, assemblers, true);
+
+    let tokens = tokenizer
+        .tokenize("test\ntes t\nte st\nt est")
+        .unwrap_or_else(|e| {
+            println!("ERROR: {}", e);
+            exit(1);
+        });
+
+    for token in &tokens {
+        print!("{}", token.content);
+    }
+    println!();
 
-impl<'a> Token<'a> {
-    pub fn new(ty: &'a str, content: &'a str, line: usize, start: usize, end: usize) -> Self {
-        Token {
-            ty,
-            content,
-            line,
-            start,
-            end,
-        }
+    match tokenizer.assemble("lines") {
+        Ok(ast) => print!("{}", ast),
+        Err(e) => println!("ERROR: {}", e),
     }
 }
 
-pub struct TFLA<'a> {
-    pub searchers: Vec<(&'a str, Regex)>,
-    pub assemblers: Vec<(&'a str, Vec<&'a str>)>,
-    pub ignore_spaces: bool,
-
-    in_line_comment: bool,
-    in_block_comment: bool,
+fn run_exaust(exaust: &str, input: &str) {
+    let exaust = Exaust::open(exaust).unwrap_or_else(|e| {
+        println!("ERROR: {}", e);
+        exit(1);
+    });
+    let code = fs::read_to_string(input).expect("Can't open the input file");
 
-    line: usize,
-    row: usize,
+    let mut tokenizer = TFLA::from_exaust(&exaust, true).unwrap_or_else(|e| {
+        println!("ERROR: {}", e);
+        exit(1);
+    });
 
-    pub tokens: Vec<Token<'a>>,
-}
+    tokenizer.recover_errors = true;
+    let _ = tokenizer.tokenize(&code);
 
-impl<'a> TFLA<'a> {
-    pub fn new(
-        searchers: Vec<(&'a str, &'a str)>,
-        assemblers: Vec<(&'a str, Vec<&'a str>)>,
-        ignore_spaces: bool,
-    ) -> Self {
-        let mut s: Vec<(&'a str, Regex)> = vec![];
-
-        for (a, b) in &searchers {
-            let c = Regex::new(b).unwrap();
-            s.push((a, c));
-        }
+    for e in &tokenizer.errors {
+        println!("ERROR: {}", e);
+    }
 
-        TFLA {
-            searchers: s,
-            assemblers,
-            ignore_spaces,
-            in_block_comment: false,
-            in_line_comment: false,
-            line: 1,
-            row: 1,
-            tokens: vec![],
+    for token in &tokenizer.tokens {
+        match &token.value {
+            Some(value) => println!(
+                "[{}] {:?} = {:?} |{} row {}|",
+                token.ty, token.content, value, token.line, token.start
+            ),
+            None => println!(
+                "[{}] {:?} |{} row {}|",
+                token.ty, token.content, token.line, token.start
+            ),
         }
     }
 
-    fn found_tokens(&mut self, code: &'a str) -> &'a str {
-        for searcher in &self.searchers {
-            if let Some(caps) = searcher.1.captures(&code) {
-                let cap = caps.get(0).unwrap();
-                let end = cap.end();
-                let content: &'a str = &code[..end];
-
-                let tk: Token<'a> =
-                    Token::new(searcher.0, content, self.line, self.row, self.row + end);
-
-                self.row += end + 1;
-                let l_type = searcher.0;
-
-                if l_type == "NEW_LINE" {
-                    self.row = 1;
-                    self.line += 1;
-
-                    self.in_line_comment = false;
-                } else if l_type == "LINE_COMMENT" {
-                    self.in_line_comment = true;
-                } else if l_type == "BLOCK_COMMENT" {
-                    self.in_block_comment = !self.in_block_comment;
-                } else if l_type == "BLOCK_COMMENT_OPEN" || l_type == "BLOCK_COMMENT_CLOSE" {
-                    self.in_block_comment = l_type == "BLOCK_COMMENT_OPEN";
-                }
-
-                if self.in_block_comment || self.in_line_comment {
-                    return &code[1..];
-                }
-
-                let slice = tk.end - tk.start;
-                if searcher.0 == "SPACE" && self.ignore_spaces {
-                } else {
-                    self.tokens.push(tk);
-                }
-
-                return &code[slice..];
+    if let (true, Some((root, _))) = (tokenizer.earley, tokenizer.assemblers.first()) {
+        match tokenizer.assemble_forest(root) {
+            Ok(forest) if forest.is_ambiguous() => {
+                println!(
+                    "The tokens have {} trees, the first one is:",
+                    forest.count()
+                );
+                print!("{}", forest.tree());
             }
+            Ok(forest) => print!("{}", forest.tree()),
+            Err(e) => println!("ERROR: {}", e),
+        }
+    } else if tokenizer.lr_table.is_some() {
+        match tokenizer.assemble_lr() {
+            Ok(ast) => print!("{}", ast),
+            Err(e) => println!("ERROR: {}", e),
         }
+    } else if let Some((root, _)) = tokenizer.assemblers.first() {
+        match tokenizer.assemble(root) {
+            Ok(ast) => print!("{}", ast),
+            Err(e) => println!("ERROR: {}", e),
+        }
+    }
+}
 
-        println!(
-            "ERROR: Any token match found.\nRest of code to tokenize:\n{}",
-            code
+/// `tfla tokenize [--format text|json|jsonl|bin] <exaust> <input>`, the errors
+/// go to stderr so the tokens on stdout can be piped.
+fn tokenize(args: &[String]) {
+    let (format, files) = match args {
+        [flag, format, files @ ..] if flag == "--format" => (format.as_str(), files),
+        files => ("text", files),
+    };
+
+    let [exaust, input] = files else {
+        eprintln!("Usage: tfla tokenize [--format text|json|jsonl|bin] <exaust> <input>");
+        exit(1);
+    };
+
+    if !["text", "json", "jsonl", "bin"].contains(&format) {
+        eprintln!(
+            "ERROR: Unknown format {}, use text, json, jsonl or bin",
+            format
         );
-        return "";
+        exit(1);
     }
 
-    pub fn tokenize(&mut self, source: &'a str) {
-        let mut code = source;
-        let mut code_: &'a str = &mut code;
+    let exaust = Exaust::open(exaust).unwrap_or_else(|e| {
+        eprintln!("ERROR: {}", e);
+        exit(1);
+    });
+    let code = fs::read_to_string(input).expect("Can't open the input file");
 
-        while code_ != "" {
-            code_ = self.found_tokens(code_);
-        }
+    let mut tokenizer = TFLA::from_exaust(&exaust, true).unwrap_or_else(|e| {
+        eprintln!("ERROR: {}", e);
+        exit(1);
+    });
+
+    tokenizer.recover_errors = true;
+    let _ = tokenizer.tokenize(&code);
+
+    for e in &tokenizer.errors {
+        eprintln!("ERROR: {}", e);
+    }
+
+    let mut out = io::stdout().lock();
+    let res = match format {
+        "text" => tokenizer.tokens.iter().try_for_each(|token| {
+            writeln!(
+                out,
+                "[{}] {:?} |{} row {}|",
+                token.ty, token.content, token.line, token.start
+            )
+        }),
+        "json" => serde_json::to_writer(&mut out, &tokenizer.tokens)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(out)),
+        "jsonl" => tokenizer.tokens.iter().try_for_each(|token| {
+            serde_json::to_writer(&mut out, token).map_err(io::Error::from)?;
+            writeln!(out)
+        }),
+        "bin" => bincode::serialize_into(&mut out, &tokenizer.tokens).map_err(io::Error::other),
+        _ => unreachable!(),
+    };
+
+    if let Err(e) = res {
+        eprintln!("ERROR: {}", e);
+        exit(1);
     }
 }
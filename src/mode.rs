@@ -0,0 +1,93 @@
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::{hybrid, meta, Anchored, Input, MatchKind};
+
+/// The mode the tokenization starts in. Unless a mode with this name is
+/// added, it has all the searchers.
+pub const DEFAULT_MODE: &str = "default";
+
+/// What happens to the mode stack when a searcher matches, see
+/// `TFLA::add_action`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ModeAction<'a> {
+    /// Enters the mode, going back to the current one on `Pop`.
+    Push(&'a str),
+    /// Goes back to the mode active before the last `Push`. In the first
+    /// mode it does nothing.
+    Pop,
+    /// Replaces the current mode.
+    Switch(&'a str),
+}
+
+/// A named set of searchers, only the searchers of the active mode are tried.
+pub(crate) struct Mode<'a> {
+    pub name: &'a str,
+    /// The indexes in `TFLA::searchers`, the automata patterns are in the
+    /// same order.
+    pub searchers: Vec<usize>,
+    pub automaton: meta::Regex,
+    /// The same automaton, but reporting every searcher that matches.
+    pub automaton_all: meta::Regex,
+    /// A lazy DFA of all the searchers, to know if they could match more
+    /// code, see `Mode::can_extend`.
+    pub prefixes: Option<DFA>,
+}
+
+impl<'a> Mode<'a> {
+    /// Compiles the searchers of the mode, their regexes must be already
+    /// checked.
+    pub fn new(name: &'a str, searchers: Vec<usize>, regexes: &[(&'a str, &'a str)]) -> Self {
+        let patterns: Vec<&str> = searchers.iter().map(|i| regexes[*i].1).collect();
+        let build = |kind: MatchKind| {
+            meta::Builder::new()
+                .configure(meta::Config::new().match_kind(kind))
+                .build_many(&patterns)
+                .expect("the searchers regexes are already checked")
+        };
+
+        let prefixes = DFA::builder()
+            .configure(
+                hybrid::dfa::Config::new()
+                    .match_kind(MatchKind::All)
+                    .unicode_word_boundary(true),
+            )
+            .build_many(&patterns)
+            .ok();
+
+        Mode {
+            name,
+            automaton: build(MatchKind::LeftmostFirst),
+            automaton_all: build(MatchKind::All),
+            prefixes,
+            searchers,
+        }
+    }
+
+    pub fn create_cache(&self) -> Option<Cache> {
+        self.prefixes.as_ref().map(|dfa| dfa.create_cache())
+    }
+
+    /// If some searcher still matches after all the code, so more code could
+    /// make its match longer than the one found.
+    ///
+    /// When the lazy DFA can't tell, like with `\b` before a non-ASCII char,
+    /// it says no.
+    pub fn can_extend(&self, cache: Option<&mut Cache>, code: &str) -> bool {
+        let (Some(dfa), Some(cache)) = (&self.prefixes, cache) else {
+            return false;
+        };
+
+        let input = Input::new(code).anchored(Anchored::Yes);
+        let Ok(mut state) = dfa.start_state_forward(cache, &input) else {
+            return false;
+        };
+
+        for byte in code.bytes() {
+            state = match dfa.next_state(cache, state, byte) {
+                Ok(state) if !state.is_dead() && !state.is_quit() => state,
+                _ => return false,
+            };
+        }
+
+        true
+    }
+}
@@ -0,0 +1,39 @@
+/// The type of the token found when a line is more indented than the last
+/// one.
+pub const INDENT: &str = "INDENT";
+/// The type of the tokens found for each indentation level closed by a line
+/// less indented than the last one, and at the end of the code.
+pub const DEDENT: &str = "DEDENT";
+
+/// The offside rule, like in Python: the indentation of each line opens or
+/// closes blocks with `INDENT` and `DEDENT` tokens, see `TFLA::offside`.
+///
+/// The blank lines and the lines with just a comment don't change the
+/// indentation. A line less indented than the last one, but not as indented
+/// as any open block, is an error.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Offside {
+    /// How many columns a tab goes to, rounding to the next multiple.
+    pub tab_width: usize,
+}
+
+impl Default for Offside {
+    fn default() -> Self {
+        Offside { tab_width: 8 }
+    }
+}
+
+impl Offside {
+    pub fn new(tab_width: usize) -> Self {
+        Offside { tab_width }
+    }
+
+    /// The width of the spaces and tabs at the start of a line.
+    pub fn width(&self, indentation: &str) -> usize {
+        indentation.chars().fold(0, |width, c| match c {
+            '\t' if self.tab_width > 0 => (width / self.tab_width + 1) * self.tab_width,
+            '\t' => width,
+            _ => width + 1,
+        })
+    }
+}
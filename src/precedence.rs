@@ -0,0 +1,40 @@
+/// How the operators of the same precedence associate, see [`Precedence`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+    /// `a < b < c` is an error.
+    NonAssoc,
+}
+
+impl Assoc {
+    /// The associativity named in TFLAC, like `%left`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Assoc::Left),
+            "right" => Some(Assoc::Right),
+            "nonassoc" => Some(Assoc::NonAssoc),
+            _ => None,
+        }
+    }
+}
+
+/// Operators with the same precedence and associativity, written like the
+/// arguments of the assemblers, as `li-"+"` or `se-Plus`. See
+/// `TFLA::precedences`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precedence<'a> {
+    pub assoc: Assoc,
+    pub operators: Vec<&'a str>,
+}
+
+impl<'a> Precedence<'a> {
+    pub fn new(assoc: Assoc, operators: &[&'a str]) -> Self {
+        Precedence {
+            assoc,
+            operators: operators.to_vec(),
+        }
+    }
+}
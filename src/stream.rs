@@ -0,0 +1,173 @@
+use std::fmt;
+use std::io::{self, BufRead};
+
+use regex_automata::hybrid::dfa::Cache;
+
+use crate::{OwnedToken, TokenizeError, TokenizeState, TFLA};
+
+#[derive(Debug)]
+pub enum StreamError {
+    /// The reader failed or the code isn't valid UTF-8.
+    Io(io::Error),
+    Tokenize(TokenizeError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "Can't read the code: {}", e),
+            StreamError::Tokenize(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl From<TokenizeError> for StreamError {
+    fn from(e: TokenizeError) -> Self {
+        StreamError::Tokenize(e)
+    }
+}
+
+/// An iterator over the tokens of the code read from a `BufRead`, created by
+/// [`TFLA::stream`].
+///
+/// Only the code of the token being searched is kept in memory, up to
+/// `MAX_LOOKAHEAD` bytes when no searcher matches yet or some searcher could
+/// still match more.
+pub struct TokenStream<'t, 'a, R> {
+    tfla: &'t TFLA<'a>,
+    reader: R,
+    state: TokenizeState,
+
+    buffer: String,
+    /// Where the code not tokenized yet starts in `buffer`.
+    pos: usize,
+    /// The byte offset of `buffer` in the whole code.
+    offset: usize,
+
+    eof: bool,
+    failed: bool,
+
+    /// The caches of the lazy DFAs of each mode.
+    caches: Vec<Option<Cache>>,
+}
+
+impl<'t, 'a, R: BufRead> TokenStream<'t, 'a, R> {
+    /// How much code is read ahead looking for a match before it's given up.
+    pub const MAX_LOOKAHEAD: usize = 1 << 20;
+
+    pub fn new(tfla: &'t TFLA<'a>, reader: R) -> Self {
+        TokenStream {
+            tfla,
+            reader,
+            state: TokenizeState::new(),
+            buffer: String::new(),
+            pos: 0,
+            offset: 0,
+            eof: false,
+            failed: false,
+            caches: tfla.modes.iter().map(|mode| mode.create_cache()).collect(),
+        }
+    }
+
+    /// The errors recovered so far, see `TFLA::recover_errors`.
+    pub fn errors(&self) -> &[TokenizeError] {
+        &self.state.errors
+    }
+
+    /// Reads the next line of code into the buffer, dropping the code
+    /// already tokenized.
+    fn read(&mut self) -> io::Result<()> {
+        self.buffer.drain(..self.pos);
+        self.offset += self.pos;
+        self.pos = 0;
+
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            self.eof = true;
+        }
+
+        Ok(())
+    }
+
+    fn next_token(&mut self) -> Result<Option<OwnedToken>, StreamError> {
+        loop {
+            let code = &self.buffer[self.pos..];
+
+            if code.is_empty() {
+                if self.eof {
+                    let offset = self.offset + self.pos;
+                    let tk = self.tfla.offside_end(&mut self.state, code, offset);
+
+                    return Ok(tk.map(|t| t.into_owned()));
+                }
+
+                self.read()?;
+                continue;
+            }
+
+            // The lines are read whole, so the indentation is never cut.
+            let offset = self.offset + self.pos;
+            if let Some(tk) = self.tfla.offside_token(&mut self.state, code, offset)? {
+                return Ok(Some(tk.into_owned()));
+            }
+
+            let found = self.tfla.search(&self.state, code);
+
+            // More code may make the match longer or make a searcher match,
+            // unless there is no more code to read. The match is longer if it
+            // ends with the code or some searcher could still match past it,
+            // like a `{[^}]*}` found as a `{` in its first line.
+            let sure = match found {
+                Some(found) => {
+                    let mode = self.state.mode();
+                    let cache = self.caches[mode].as_mut();
+
+                    found.end < code.len() && !self.tfla.modes[mode].can_extend(cache, code)
+                }
+                None => false,
+            };
+
+            if !sure && !self.eof && code.len() < Self::MAX_LOOKAHEAD {
+                self.read()?;
+                continue;
+            }
+
+            let offset = self.offset + self.pos;
+            let (tk, end) = self
+                .tfla
+                .found_tokens(&mut self.state, code, offset, found)?;
+            let tk = tk.map(|t| t.into_owned());
+
+            self.pos += end;
+
+            if tk.is_some() {
+                return Ok(tk);
+            }
+        }
+    }
+}
+
+impl<'t, 'a, R: BufRead> Iterator for TokenStream<'t, 'a, R> {
+    type Item = Result<OwnedToken, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.next_token() {
+            Ok(tk) => tk.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
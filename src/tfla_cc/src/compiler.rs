@@ -1,32 +1,93 @@
-mod tfla;
-use tfla::{Token, TFLA};
+use tfla::comment::{BLOCK_COMMENT, LINE_COMMENT};
+use tfla::offside::{DEDENT, INDENT};
+use tfla::{Comment, Converter, Token, DEFAULT_MODE, ERROR, TFLA};
 
+use std::cell::Cell;
 use std::process::exit;
 
+use crate::ebnf::{self, Lowering};
+use crate::lalr::{show_symbol, Lalr, Production};
+
 pub struct Searcher<'a> {
     name: &'a str,
     regex: &'a str,
+
+    /// The mode action after "->", if any.
+    action: Option<Vec<&'a str>>,
+    /// The converter after "=>", if any.
+    converter: Option<Vec<&'a str>>,
+    /// The last of "->" or "=>" found.
+    trailer: &'a str,
 }
 
 impl<'a> Searcher<'a> {
     pub fn new(name: &'a str, regex: &'a str) -> Self {
-        Searcher { name, regex }
+        Searcher {
+            name,
+            regex,
+            action: None,
+            converter: None,
+            trailer: "",
+        }
     }
 
     pub fn mount(&self) -> String {
-        let res = format!("se {} {}", &self.name[..], &self.regex[..]);
+        let res = format!("se {} {}", self.name, self.regex);
         res
     }
 
+    pub fn mount_action(&self, line: usize) -> Option<String> {
+        let action = self.action.as_ref()?;
+
+        match &action[..] {
+            ["push", mode] | ["switch", mode] => {
+                Some(format!("ac {} {} {}", self.name, action[0], mode))
+            }
+            ["pop"] => Some(format!("ac {} pop", self.name)),
+            _ => {
+                println!("\nERROR: The Searcher [{}] have an invalid mode action, use \"push <mode>\", \"pop\" or \"switch <mode>\". |{}|\n", self.name, line);
+                exit(1);
+            }
+        }
+    }
+
     pub fn transform(&self) -> (&'a str, &'a str) {
-        let name = &self.name[..];
-        let regex = &self.regex[..];
+        let name = self.name;
+        let regex = self.regex;
 
         (name, regex)
     }
 
+    pub fn mount_converter(&self, line: usize) -> Option<String> {
+        let converter = self.converter.as_ref()?;
+
+        match &converter[..] {
+            [name] if Converter::from_name(name).is_some() => {
+                Some(format!("cv {} {}", self.name, name))
+            }
+            _ => {
+                println!("\nERROR: The Searcher [{}] have an invalid converter, use \"int\", \"float\", \"string\" or \"bool\". |{}|\n", self.name, line);
+                exit(1);
+            }
+        }
+    }
+
     pub fn add_arbitrary(&mut self, regex: &'a str) {
-        self.regex = regex;
+        if regex == "->" {
+            self.action = Some(vec![]);
+            self.trailer = regex;
+            return;
+        } else if regex == "=>" {
+            self.converter = Some(vec![]);
+            self.trailer = regex;
+            return;
+        }
+
+        match self.trailer {
+            "->" => self.action.get_or_insert_with(Vec::new).push(regex),
+            "=>" => self.converter.get_or_insert_with(Vec::new).push(regex),
+            _ => self.regex = regex,
+        }
     }
 }
 
@@ -39,7 +100,11 @@ pub struct Assembler {
 }
 impl Assembler {
     pub fn new(name: String, arbitrary: Vec<String>) -> Self {
-        Assembler { name, arbitrary, line: 0 }
+        Assembler {
+            name,
+            arbitrary,
+            line: 0,
+        }
     }
 
     pub fn from(text: String) -> Self {
@@ -48,35 +113,62 @@ impl Assembler {
         let name: String = parts[2].clone();
         let arbitrary: Vec<String> = parts[3..].to_vec();
 
-        Assembler { name: String::from(name), arbitrary, line }
+        Assembler {
+            name,
+            arbitrary,
+            line,
+        }
     }
 
+    /// The groups, like `(a | b)` or `(args)","+`, are mounted as new
+    /// Assemblers in the next lines, see [`Lowering`].
     pub fn mount(&self) -> String {
-        let mut res = format!("as {}", &self.name[..]);
+        let expressions = match ebnf::parse(&self.arbitrary) {
+            Ok(expressions) => expressions,
+            Err(e) => {
+                println!(
+                    "\nERROR: {} in the Assembler <{}>. |{}|\n",
+                    e, self.name, self.line
+                );
+                exit(1);
+            }
+        };
 
-        for a in &self.arbitrary {
-            let last = a.len() - 1;
+        if expressions.is_empty() {
+            return format!("as {}", &self.name[..]);
+        }
 
-            if a.starts_with("<") && a.len() > 2 {
-                res = format!("{} as-{}", res, &a[1..last]);
-            } else if a.starts_with("[") && a.len() > 2 {
-                res = format!("{} se-{}", res, &a[1..last]);
-            } else if a.starts_with(":") && a.len() > 2 {
-                res = format!("{} sy-{}", res, &a[1..last]);
-            } else {
-                res = format!("{} li-{}", res, a);
-            }
+        let mut lowering = Lowering::new(&self.name, self.line);
+        let mut res = format!(
+            "as {} {}",
+            &self.name[..],
+            lowering.sequence(&expressions).join(" ")
+        );
+
+        for (name, args) in &lowering.rules {
+            res += &format!("\n{} as {} {}", self.line, name, args.join(" "));
         }
 
         res
     }
 
-    pub fn add_arbitrary(&mut self, arbitrary: &str) {
-        self.arbitrary.push(arbitrary.to_string());
+    /// The argument as written in the exaust, like `as-expr` for `<expr>`.
+    pub fn mount_argument(a: &str) -> String {
+        let last = a.len() - 1;
+
+        if a.starts_with("<") && a.len() > 2 {
+            format!("as-{}", &a[1..last])
+        } else if a.starts_with("[") && a.len() > 2 {
+            format!("se-{}", &a[1..last])
+        } else if a.starts_with(":") && a.len() > 2 {
+            format!("sy-{}", &a[1..last])
+        } else {
+            format!("li-{}", a)
+        }
     }
 
-    pub fn have_arbitrary(&self, arbitrary: &str) -> bool {
-        self.arbitrary.contains(&arbitrary.to_string())
+    pub fn add_arbitrary(&mut self, arbitrary: &str) {
+        self.arbitrary.push(arbitrary.to_string());
     }
 
     pub fn arbitrary(&self) -> Vec<String> {
@@ -90,7 +182,7 @@ impl Assembler {
     pub fn contain(assemblers: &Vec<Assembler>, value: &str) -> bool {
         for a in assemblers {
             if a.name() == value {
-                return true
+                return true;
             }
         }
 
@@ -108,7 +200,7 @@ impl<'a> Symbol<'a> {
     }
 
     pub fn mount(&self) -> String {
-        let res = format!("sy {} {}", &self.name[..], &self.arbitrary[..]);
+        let res = format!("sy {} {}", self.name, self.arbitrary);
 
         res
     }
@@ -118,9 +210,104 @@ impl<'a> Symbol<'a> {
     }
 }
 
+pub struct Directive<'a> {
+    name: &'a str,
+    arbitrary: Vec<&'a str>,
+
+    line: usize,
+}
+impl<'a> Directive<'a> {
+    pub fn new(name: &'a str, line: usize) -> Self {
+        Directive {
+            name,
+            arbitrary: vec![],
+            line,
+        }
+    }
+
+    /// The Searcher is the last one declared before the directive.
+    pub fn mount(&self, searcher: &str) -> String {
+        match (self.name, &self.arbitrary[..]) {
+            ("comment", [open]) => format!("co {} {}", LINE_COMMENT, open),
+            ("comment", [open, close]) => format!("co {} {} {}", BLOCK_COMMENT, open, close),
+            ("comment", [open, close, "nested"]) => {
+                format!("co {} {} {} nested", BLOCK_COMMENT, open, close)
+            }
+            ("mode", [name]) => format!("mo {}", name),
+            ("mode", _) => {
+                println!(
+                    "\nERROR: The directive {{mode}} takes the name of the mode. |{}|\n",
+                    self.line
+                );
+                exit(1);
+            }
+            ("keywords", [first, keywords @ ..])
+                if first.len() > 2
+                    && first.starts_with('[')
+                    && first.ends_with(']')
+                    && !keywords.is_empty() =>
+            {
+                format!("kw {} {}", &first[1..first.len() - 1], keywords.join(" "))
+            }
+            ("keywords", keywords) if !keywords.is_empty() && !searcher.is_empty() => {
+                format!("kw {} {}", searcher, keywords.join(" "))
+            }
+            ("keywords", _) => {
+                println!("\nERROR: The directive {{keywords}} takes the keywords, after the Searcher they retag if it isn't the last Searcher declared. |{}|\n", self.line);
+                exit(1);
+            }
+            ("left" | "right" | "nonassoc", operators) if !operators.is_empty() => {
+                let operators: Vec<String> = operators
+                    .iter()
+                    .map(|o| Assembler::mount_argument(o))
+                    .collect();
+
+                format!("pr {} {}", self.name, operators.join(" "))
+            }
+            ("left" | "right" | "nonassoc", _) => {
+                println!(
+                    "\nERROR: The precedence %{} takes the operators, like \"+\" or [Plus]. |{}|\n",
+                    self.name, self.line
+                );
+                exit(1);
+            }
+            ("parser", ["earley"]) => "pa earley".to_string(),
+            ("parser", _) => {
+                println!("\nERROR: The directive {{parser}} takes \"earley\", to assemble ambiguous grammars. |{}|\n", self.line);
+                exit(1);
+            }
+            ("offside", []) => "of tab_width 8".to_string(),
+            ("offside", [tab_width]) if tab_width.parse::<usize>().is_ok() => {
+                format!("of tab_width {}", tab_width)
+            }
+            ("offside", _) => {
+                println!("\nERROR: The directive {{offside}} takes just the width of a tab, if it isn't 8. |{}|\n", self.line);
+                exit(1);
+            }
+            ("comment", _) => {
+                println!("\nERROR: The directive {{comment}} takes an open and an optional close regex, followed by \"nested\" if the comment can be nested. |{}|\n", self.line);
+                exit(1);
+            }
+            (name, _) => {
+                println!(
+                    "\nERROR: The directive {{{}}} don't exists. |{}|\n",
+                    name, self.line
+                );
+                exit(1);
+            }
+        }
+    }
+
+    pub fn add_arbitrary(&mut self, arbitrary: &'a str) {
+        self.arbitrary.push(arbitrary);
+    }
+}
+
 pub struct TflaCC<'a> {
     code: &'a str,
     searchers: Vec<(&'a str, &'a str)>,
+    /// The WARNs shown, the errors exit.
+    warns: Cell<usize>,
 }
 
 impl<'a> TflaCC<'a> {
@@ -128,35 +315,49 @@ impl<'a> TflaCC<'a> {
         TflaCC {
             code,
             searchers,
+            warns: Cell::new(0),
         }
     }
 
-    pub fn tokenize(&self) -> Vec<Token<'a>> {
-        let mut tokens: Vec<Token> = vec![];
-
-        let mut cc: TFLA = TFLA::new(self.searchers.clone(), vec![], true);
+    pub fn warns(&self) -> usize {
+        self.warns.get()
+    }
 
-        cc.tokenize(self.code);
+    fn warn(&self) {
+        self.warns.set(self.warns.get() + 1);
+    }
 
-        for t in &cc.tokens {
-            tokens.push(*t);
+    pub fn tokenize(&self) -> Vec<Token<'a>> {
+        let mut cc: TFLA = TFLA::new(self.searchers.clone(), vec![], true);
+        cc.add_comment(Comment::line("comment", r"\-\-")).unwrap();
+        cc.keep_comments = true;
+
+        match cc.tokenize(self.code) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                println!(
+                    "\nERROR: Can't tokenize \"{}\". |{} row {}|\n",
+                    e.snippet, e.line, e.column
+                );
+                exit(1);
+            }
         }
-
-        tokens
     }
 
     fn add_to(
         &self,
-        active: &String,
+        active: &str,
         value: &'a str,
         se: &mut Searcher<'a>,
         as_: &mut Assembler,
         sy: &mut Symbol<'a>,
+        di: &mut Directive<'a>,
     ) {
-        match &active[..] {
+        match active {
             "searcher" => se.add_arbitrary(value),
             "assembler" => as_.add_arbitrary(value),
             "symbol" => sy.add_arbitrary(value),
+            "directive" | "precedence" => di.add_arbitrary(value),
             &_ => (),
         }
     }
@@ -167,15 +368,25 @@ impl<'a> TflaCC<'a> {
         se: &mut Searcher,
         as_: &mut Assembler,
         sy: &mut Symbol,
+        di: &mut Directive,
         line: usize,
     ) -> String {
-        let res;
+        let mut res;
         if active == "searcher" {
-            res = format!("\n{} {}", line, se.mount()).to_string();
+            res = format!("\n{} {}", line, se.mount());
+
+            for trailer in [se.mount_action(line), se.mount_converter(line)]
+                .into_iter()
+                .flatten()
+            {
+                res += &format!("\n{} {}", line, trailer);
+            }
         } else if active == "assembler" {
             res = format!("\n{} {}", line, as_.mount()).to_string();
         } else if active == "symbol" {
             res = format!("\n{} {}", line, sy.mount()).to_string();
+        } else if active == "directive" || active == "precedence" {
+            res = format!("\n{} {}", line, di.mount(se.name)).to_string();
         } else {
             return String::new();
         }
@@ -185,14 +396,16 @@ impl<'a> TflaCC<'a> {
     }
 
     fn parse(&self) -> String {
-        let mut res = String::from(r"0 sy nwl (\r)?\n
+        let mut res = String::from(
+            r"0 sy nwl (\r)?\n
 0 sy eof \z
 0 sy eol $
-0 sy tab \t 
-0 sy noh \0
-0 sy num \d+");
+0 sy tab \t
+0 sy noh \x00
+0 sy num \d+",
+        );
 
-        let tokens = self.tokenize();
+        let tokens = self.join_arguments(self.tokenize());
 
         let mut token_type: String = String::from("");
         let mut tk_num: i16 = 0;
@@ -202,6 +415,7 @@ impl<'a> TflaCC<'a> {
         let mut searcher: Searcher = Searcher::new("", "");
         let mut assembler: Assembler = Assembler::new("".to_string(), vec![]);
         let mut symbol: Symbol = Symbol::new("", "");
+        let mut directive: Directive = Directive::new("", 0);
 
         for Token {
             ref content,
@@ -211,7 +425,6 @@ impl<'a> TflaCC<'a> {
             ..
         } in &tokens
         {
-            
             tk_num += 1;
             if ty == &"NEW_LINE" {
                 tk_num = 0;
@@ -228,6 +441,7 @@ impl<'a> TflaCC<'a> {
                             &mut searcher,
                             &mut assembler,
                             &mut symbol,
+                            &mut directive,
                             def_line,
                         );
                         let last = content.len() - 1;
@@ -241,11 +455,13 @@ impl<'a> TflaCC<'a> {
                             &mut searcher,
                             &mut assembler,
                             &mut symbol,
+                            &mut directive,
                             def_line,
                         );
                         let last = content.len() - 1;
                         token_type = "assembler".to_string();
                         assembler = Assembler::new(content[1..last].to_string(), vec![]);
+                        assembler.line = *line;
                         def_line = *line;
                     }
                     "symbol" => {
@@ -254,6 +470,7 @@ impl<'a> TflaCC<'a> {
                             &mut searcher,
                             &mut assembler,
                             &mut symbol,
+                            &mut directive,
                             def_line,
                         );
                         let last = content.len() - 1;
@@ -261,8 +478,36 @@ impl<'a> TflaCC<'a> {
                         symbol = Symbol::new(&content[1..last], "");
                         def_line = *line;
                     }
+                    "directive" => {
+                        res += &self.mount_this(
+                            &mut token_type,
+                            &mut searcher,
+                            &mut assembler,
+                            &mut symbol,
+                            &mut directive,
+                            def_line,
+                        );
+                        let last = content.len() - 1;
+                        token_type = "directive".to_string();
+                        directive = Directive::new(&content[1..last], *line);
+                        def_line = *line;
+                    }
+                    "precedence" => {
+                        res += &self.mount_this(
+                            &mut token_type,
+                            &mut searcher,
+                            &mut assembler,
+                            &mut symbol,
+                            &mut directive,
+                            def_line,
+                        );
+                        token_type = "precedence".to_string();
+                        directive = Directive::new(&content[1..], *line);
+                        def_line = *line;
+                    }
                     "colon" | "pipe" => {
                         if token_type != "assembler" {
+                            self.warn();
                             println!("\nWARN: The token \":\" was typing an AB, this operation was ocourring in a {}, but only Assemblers support this action. (IGNORED DURING ANALYSIS) |{} row {}|\n", token_type, line, start);
                             continue;
                         }
@@ -276,13 +521,17 @@ impl<'a> TflaCC<'a> {
                         exit(1);
                     }
                 }
-            } else if tk_num > 2 && !in_comment {
+            } else if *ty == "comment" && !in_comment {
+                println!("\nERROR: The comment is invading another AB, comments must start the line. |{} row {}|\n", line, start);
+                exit(1);
+            } else if (tk_num > 2 || (tk_num == 2 && token_type == "precedence")) && !in_comment {
                 self.add_to(
                     &token_type,
-                    &content,
+                    content,
                     &mut searcher,
                     &mut assembler,
                     &mut symbol,
+                    &mut directive,
                 );
             }
         }
@@ -291,53 +540,94 @@ impl<'a> TflaCC<'a> {
             &mut searcher,
             &mut assembler,
             &mut symbol,
+            &mut directive,
             def_line,
         );
 
-
-        res.trim().to_string()
+        self.group_modes(res.trim())
     }
 
-    fn get_assembler(&self, name: &str, assemblers: &Vec<Assembler>) -> Vec<Assembler> {
-        let mut res: Vec<Assembler> = vec![];
+    /// Joins the arguments written together, like the regex `^(\w|_)+`, that
+    /// the searchers of the groups split, unless they're arguments of an
+    /// Assembler.
+    fn join_arguments(&self, tokens: Vec<Token<'a>>) -> Vec<Token<'a>> {
+        let mut res: Vec<Token<'a>> = vec![];
+        let mut first = "";
+        let mut tk_num = 0;
 
-        for a in assemblers {
-            if a.name() == name {
-                res.push(a.clone());
+        for token in tokens {
+            tk_num += 1;
+            if token.ty == "NEW_LINE" {
+                tk_num = 0;
+            } else if tk_num == 1 {
+                first = token.ty;
+            }
+
+            // The arguments of a precedence start after the %left.
+            let arguments = if first == "precedence" { 2 } else { 3 };
+
+            match res.last_mut() {
+                Some(last)
+                    if tk_num > arguments
+                        && first != "assembler"
+                        && last.range().end == token.offset =>
+                {
+                    last.content = &self.code[last.offset..token.range().end];
+                    last.end = token.end;
+                    last.ty = "entity";
+                    tk_num -= 1;
+                }
+                _ => res.push(token),
             }
         }
 
         res
     }
 
-    fn found_circular_exp(&self, name: String, assembler: &Assembler, assemblers: &Vec<Assembler>) {
-        if assembler.have_arbitrary(&format!("as-{}", name)[..]) {
-            if assembler.name() == name {
-                println!("\nImpossible Case! The Assembler <{}> have a expansion to itself! |{}|", name, assembler.line);
-                exit(0);
-            }else {
-                println!("\nImpossible Case! The Assembler <{}> is part of an circular expansion! |{}|", name, assembler.line);
-                exit(0);
-            }
-        }
+    /// Joins the Searchers after each {mode} directive in a single "mo" line
+    /// with all the Searchers of the mode. The Searchers before any {mode}
+    /// are in the default mode.
+    fn group_modes(&self, code: &str) -> String {
+        let mut modes: Vec<(&str, &str, Vec<&str>)> = vec![(DEFAULT_MODE, "0", vec![])];
+        let mut active = 0;
+        let mut lines: Vec<String> = vec![];
 
-        let arbitraries = assembler.arbitrary();
+        for line in code.split("\n") {
+            let parts: Vec<&str> = line.split(" ").collect();
 
-        for arbitrary in arbitraries {
-            if arbitrary.starts_with("as-") {
-                let definitions = self.get_assembler(&arbitrary[3..], assemblers);
+            if parts[1] == "mo" {
+                active = match modes.iter().position(|(name, ..)| *name == parts[2]) {
+                    Some(i) => i,
+                    None => {
+                        modes.push((parts[2], parts[0], vec![]));
+                        modes.len() - 1
+                    }
+                };
+                continue;
+            } else if parts[1] == "se" {
+                modes[active].2.push(parts[2]);
+            }
 
-                for def in definitions {
-                    self.found_circular_exp(name.clone(), &def, assemblers);
-                }
+            lines.push(line.to_string());
+        }
+
+        if modes.len() > 1 {
+            for (name, line, searchers) in modes {
+                lines.push(
+                    format!("{} mo {} {}", line, name, searchers.join(" "))
+                        .trim()
+                        .to_string(),
+                );
             }
         }
+
+        lines.join("\n")
     }
 
     fn contain(&self, assemblers: &Vec<(String, usize)>, name: &str) -> bool {
         for (a, _) in assemblers {
             if a == name {
-                return true
+                return true;
             }
         }
 
@@ -363,6 +653,10 @@ impl<'a> TflaCC<'a> {
         let mut symbols: Vec<(String, usize)> = vec![];
         let mut searchers: Vec<(String, usize)> = vec![];
         let mut assemblers: Vec<Assembler> = vec![];
+        let mut modes: Vec<String> = vec![DEFAULT_MODE.to_string()];
+        let mut actions: Vec<(String, String, usize)> = vec![];
+        let mut earley = false;
+        let mut precedences: Vec<(String, Vec<String>)> = vec![];
 
         let mut lines: Vec<String> = code.split("\n").map(|a| a.to_string()).collect();
 
@@ -375,14 +669,65 @@ impl<'a> TflaCC<'a> {
             if prefix == "sy" {
                 symbols.push((name.clone(), num_line));
             } else if prefix == "se" {
+                if [ERROR, INDENT, DEDENT].contains(&name.as_str()) {
+                    println!(
+                        "\nERROR: The Searcher [{}] have a name reserved by TFLA. |{}|",
+                        name, num_line
+                    );
+                    exit(1);
+                }
+
                 searchers.push((name.clone(), num_line));
             } else if prefix == "as" {
                 assemblers.push(Assembler::from(line.clone()));
+            } else if prefix == "mo" {
+                modes.push(name.clone());
+            } else if prefix == "pr" {
+                for operator in &parts[3..] {
+                    let value = &operator[3..];
+
+                    if (operator.starts_with("se-") && !self.contain(&searchers, value))
+                        || (operator.starts_with("sy-") && !self.contain(&symbols, value))
+                    {
+                        println!("\nERROR: The precedence %{} have the operator {}, but it's don't exists. |{}|",
+                            name, operator, num_line);
+                        exit(1);
+                    }
+                }
+
+                precedences.push((name.clone(), parts[3..].to_vec()));
+            } else if prefix == "pa" {
+                earley = true;
+            } else if prefix == "of" {
+                searchers.push(("INDENT".to_string(), num_line));
+                searchers.push(("DEDENT".to_string(), num_line));
+            } else if prefix == "kw" {
+                if !self.contain(&searchers, name) {
+                    println!("\nERROR: The directive {{keywords}} retag the Searcher [{}], but it's don't exists. |{}|",
+                        name, num_line);
+                    exit(1);
+                }
+
+                for keyword in &parts[3..] {
+                    searchers.push((keyword.clone(), num_line));
+                }
+            } else if prefix == "ac" && parts.len() > 4 {
+                actions.push((name.clone(), parts[4].clone(), num_line));
             }
 
             *line = parts[1..].join(" ");
         }
 
+        for (searcher, mode, line) in &actions {
+            if !modes.contains(mode) {
+                println!(
+                    "\nERROR: The Searcher [{}] enter the mode {}, but it's don't exists. |{}|",
+                    searcher, mode, line
+                );
+                exit(1);
+            }
+        }
+
         for assembler in &assemblers.clone() {
             let name = assembler.name();
 
@@ -392,30 +737,103 @@ impl<'a> TflaCC<'a> {
                 let prefix = &arbitrary[..3];
                 let value = &arbitrary[3..];
 
-                if prefix == "sy-" && !self.contain(&symbols, &value) {
+                if prefix == "sy-" && !self.contain(&symbols, value) {
                     println!("\nERROR: The Assembler <{}> have an expansion to Symbol :{}:, but it's don't exists. |{}|",
-                        name, 
+                        name,
                         value, assembler.line);
-                    exit(0); 
-                }else if prefix == "se-" && !self.contain(&searchers, &value) {
+                    exit(1);
+                } else if prefix == "se-" && !self.contain(&searchers, value) {
                     println!("\nERROR: The Assembler <{}> have an expansion to Searcher [{}], but it's don't exists. |{}|",
-                        name, 
+                        name,
+                        value, assembler.line);
+                    exit(1);
+                } else if prefix == "as-" && !Assembler::contain(&assemblers, value) {
+                    println!("\nERROR: The Assembler <{}> have an expansion to Assembler <{}>, but it's don't exists. |{}|",
+                        name,
                         value, assembler.line);
-                    exit(0); 
-                }else if prefix == "as-" {
-                    if !Assembler::contain(&assemblers, &value) {
-                        println!("\nERROR: The Assembler <{}> have an expansion to Assembler <{}>, but it's don't exists. |{}|",
-                            name, 
-                            value, assembler.line);
-
-                        exit(0);
-                    }
 
-                    self.found_circular_exp(name.clone(), &assembler, &assemblers);
+                    exit(1);
                 }
             }
         }
 
+        if !assemblers.is_empty() {
+            lines.extend(self.lr_table(&assemblers, precedences, earley));
+        }
+
         lines.join("\n")
     }
+
+    /// Builds the LALR(1) table of the Assemblers, the first one is the root.
+    /// A conflict is a warning, the table has the action chosen like in yacc,
+    /// see `Lalr::actions`. With {parser} : earley there is no table, and no
+    /// check of the grammar.
+    fn lr_table(
+        &self,
+        assemblers: &[Assembler],
+        precedences: Vec<(String, Vec<String>)>,
+        earley: bool,
+    ) -> Vec<String> {
+        // The Earley parser takes any grammar, even with cycles.
+        if earley {
+            return vec![];
+        }
+
+        let productions = assemblers
+            .iter()
+            .map(|a| Production::new(a.name(), a.arbitrary(), a.line))
+            .collect();
+
+        let lalr = Lalr::new(productions, precedences);
+
+        let cycles = lalr.cycles();
+
+        for (name, direct, line) in &cycles {
+            if *direct {
+                println!(
+                    "\nImpossible Case! The Assembler <{}> have a expansion to itself! |{}|",
+                    name, line
+                );
+            } else {
+                println!(
+                    "\nImpossible Case! The Assembler <{}> is part of an circular expansion! |{}|",
+                    name, line
+                );
+            }
+        }
+
+        if !cycles.is_empty() {
+            exit(1);
+        }
+
+        let (actions, conflicts) = lalr.actions();
+
+        for conflict in &conflicts {
+            let mut options: Vec<String> = conflict
+                .shifts
+                .iter()
+                .map(|item| format!("shift {}", lalr.show(*item)))
+                .collect();
+
+            for p in &conflict.reduces {
+                options.push(format!("reduce {}", lalr.show(lalr.reduced(*p))));
+            }
+
+            let kind = if conflict.shifts.is_empty() {
+                "Reduce/reduce"
+            } else {
+                "Shift/reduce"
+            };
+            self.warn();
+            println!(
+                "\nWARN: {} conflict in the state {} with the lookahead {}, it can {}.",
+                kind,
+                conflict.state,
+                show_symbol(&conflict.lookahead),
+                options.join(" or ")
+            );
+        }
+
+        lalr.mount(&actions)
+    }
 }
@@ -0,0 +1,261 @@
+use crate::compiler::Assembler;
+
+/// How many times a group is repeated, after its ")".
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Repeat {
+    Once,
+    /// `(args)?`
+    Optional,
+    /// `(args)...`
+    Many,
+    /// `(args)+`
+    Some,
+    /// `(args)N`
+    Times(usize),
+}
+
+/// An argument of an Assembler, or a group of options of arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Argument(String),
+    Group {
+        options: Vec<Vec<Expression>>,
+        repeat: Repeat,
+        /// The literal between the repetitions, like "," in `(args)","+`.
+        separator: Option<String>,
+    },
+}
+
+/// Parses the arguments of an Assembler, as tokenized by TFLA CC, where
+/// "(" opens a group, "|" splits its options and ")" closes it, with the
+/// separator and the repetition after it, like `)","+`.
+pub fn parse(arbitrary: &[String]) -> Result<Vec<Expression>, String> {
+    // The options of each open group, the last one is the top.
+    let mut stack: Vec<Vec<Vec<Expression>>> = vec![vec![vec![]]];
+
+    for arg in arbitrary {
+        if arg == "(" {
+            stack.push(vec![vec![]]);
+        } else if arg == "|" {
+            if stack.len() == 1 {
+                return Err("The \"|\" is outside of a group, write the options in parentheses, like (a | b)".to_string());
+            }
+
+            stack.last_mut().unwrap().push(vec![]);
+        } else if let Some(suffix) = arg.strip_prefix(")") {
+            if stack.len() == 1 {
+                return Err("The \")\" don't close any group".to_string());
+            }
+
+            let options = stack.pop().unwrap();
+            let (separator, repeat) = repetition(suffix)?;
+
+            stack
+                .last_mut()
+                .unwrap()
+                .last_mut()
+                .unwrap()
+                .push(Expression::Group {
+                    options,
+                    repeat,
+                    separator,
+                });
+        } else {
+            stack
+                .last_mut()
+                .unwrap()
+                .last_mut()
+                .unwrap()
+                .push(Expression::Argument(arg.clone()));
+        }
+    }
+
+    if stack.len() > 1 {
+        return Err("The group isn't closed, it's missing a \")\"".to_string());
+    }
+
+    Ok(stack.pop().unwrap().pop().unwrap())
+}
+
+/// The separator and the repetition after a ")".
+fn repetition(suffix: &str) -> Result<(Option<String>, Repeat), String> {
+    let (separator, repeat) = match suffix.rfind('"') {
+        Some(end) => (Some(suffix[..=end].to_string()), &suffix[end + 1..]),
+        None => (None, suffix),
+    };
+
+    let repeat = match repeat {
+        "" => Repeat::Once,
+        "?" => Repeat::Optional,
+        "..." => Repeat::Many,
+        "+" => Repeat::Some,
+        n => match n.parse::<usize>() {
+            Ok(n) => Repeat::Times(n),
+            Err(_) => {
+                return Err(format!(
+                    "The repetition \"{}\" don't exists, use ?, ..., + or a number",
+                    n
+                ))
+            }
+        },
+    };
+
+    if separator.is_some() && matches!(repeat, Repeat::Once | Repeat::Optional) {
+        return Err(
+            "The separator is just for the groups that repeat, like (args)\",\"+".to_string(),
+        );
+    }
+
+    Ok((separator, repeat))
+}
+
+/// Turns the groups of an Assembler in new Assemblers, named like
+/// `list#3.1` for the first group in the line 3, that TFLA moves the
+/// children to the parent node.
+pub struct Lowering<'n> {
+    name: &'n str,
+    line: usize,
+    count: usize,
+    /// The new Assemblers, with the arguments written like in the exaust.
+    pub rules: Vec<(String, Vec<String>)>,
+}
+
+impl<'n> Lowering<'n> {
+    pub fn new(name: &'n str, line: usize) -> Self {
+        Lowering {
+            name,
+            line,
+            count: 0,
+            rules: vec![],
+        }
+    }
+
+    /// The arguments written like in the exaust, an empty sequence is ε.
+    pub fn sequence(&mut self, expressions: &[Expression]) -> Vec<String> {
+        let mut args = vec![];
+
+        for expression in expressions {
+            match expression {
+                Expression::Argument(a) => args.push(Assembler::mount_argument(a)),
+                Expression::Group {
+                    options,
+                    repeat,
+                    separator,
+                } => {
+                    let name = self.group(options, *repeat, separator.as_deref());
+                    args.push(format!("as-{}", name));
+                }
+            }
+        }
+
+        if args.is_empty() {
+            args.push("li-ε".to_string());
+        }
+
+        args
+    }
+
+    fn new_rule(&mut self) -> String {
+        self.count += 1;
+
+        format!("{}#{}.{}", self.name, self.line, self.count)
+    }
+
+    fn push(&mut self, name: &str, args: Vec<String>) {
+        self.rules.push((name.to_string(), args));
+    }
+
+    /// The arguments of one repetition of the group, the arguments of the
+    /// option if there is just one, or else a new Assembler with the options.
+    fn item(&mut self, options: &[Vec<Expression>]) -> Vec<String> {
+        if let [option] = options {
+            return self.sequence(option);
+        }
+
+        let name = self.new_rule();
+
+        for option in options {
+            let args = self.sequence(option);
+            self.push(&name, args);
+        }
+
+        vec![format!("as-{}", name)]
+    }
+
+    /// The repetitions are right recursive, so the first option takes as
+    /// many as it can, like the options are tried.
+    fn group(
+        &mut self,
+        options: &[Vec<Expression>],
+        repeat: Repeat,
+        separator: Option<&str>,
+    ) -> String {
+        let name = self.new_rule();
+        let separator = separator.map(Assembler::mount_argument);
+
+        match repeat {
+            Repeat::Once | Repeat::Optional => {
+                for option in options {
+                    let args = self.sequence(option);
+                    self.push(&name, args);
+                }
+
+                if repeat == Repeat::Optional {
+                    self.push(&name, vec!["li-ε".to_string()]);
+                }
+            }
+            Repeat::Some => {
+                let item = self.item(options);
+                self.repeat(&name, &item, separator);
+                self.push(&name, item);
+            }
+            Repeat::Many => {
+                let item = self.item(options);
+
+                match separator {
+                    // The separator is just between the repetitions, so the
+                    // ones after the first are in another Assembler.
+                    Some(separator) => {
+                        let list = self.new_rule();
+                        self.repeat(&list, &item, Some(separator));
+                        self.push(&list, item);
+                        self.push(&name, vec![format!("as-{}", list)]);
+                    }
+                    None => self.repeat(&name, &item, None),
+                }
+
+                self.push(&name, vec!["li-ε".to_string()]);
+            }
+            Repeat::Times(n) => {
+                let item = self.item(options);
+                let mut args = vec![];
+
+                for i in 0..n {
+                    if let (true, Some(separator)) = (i > 0, &separator) {
+                        args.push(separator.clone());
+                    }
+
+                    args.extend(item.iter().cloned());
+                }
+
+                if args.is_empty() {
+                    args.push("li-ε".to_string());
+                }
+
+                self.push(&name, args);
+            }
+        }
+
+        name
+    }
+
+    /// The option `<name> : item separator <name>`, the option that ends
+    /// the repetition is pushed after it.
+    fn repeat(&mut self, name: &str, item: &[String], separator: Option<String>) {
+        let mut args = item.to_vec();
+        args.extend(separator);
+        args.push(format!("as-{}", name));
+
+        self.push(name, args);
+    }
+}
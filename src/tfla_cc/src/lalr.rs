@@ -0,0 +1,467 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use tfla::LR_END;
+
+/// An option of an Assembler, with the arguments written like in the
+/// exaust, as `as-expr` or `li-"+"`, and without the ε.
+pub struct Production {
+    pub name: String,
+    pub args: Vec<String>,
+    pub line: usize,
+}
+
+impl Production {
+    pub fn new(name: String, args: Vec<String>, line: usize) -> Self {
+        let args = args.into_iter().filter(|a| a != "li-ε").collect();
+
+        Production { name, args, line }
+    }
+}
+
+/// A production with the position of the dot in its arguments.
+type Item = (usize, usize);
+/// The items of a state with the lookaheads of each one.
+type Items = BTreeMap<Item, BTreeSet<String>>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+/// How a shift/reduce conflict is solved by the precedences, `Error` is for
+/// the `nonassoc` operators, like `a < b < c`.
+enum Resolution {
+    Shift,
+    Reduce,
+    Error,
+}
+
+/// The lookahead where a state can shift and reduce, or reduce two
+/// productions, the items are the ones that want each action.
+pub struct Conflict {
+    pub state: usize,
+    pub lookahead: String,
+    pub shifts: Vec<Item>,
+    pub reduces: Vec<usize>,
+}
+
+/// The LALR(1) automaton of the Assemblers, the first one is the root.
+///
+/// The states are built like the LR(1) ones, but the states with the same
+/// items are merged as soon as they're found, joining their lookaheads.
+pub struct Lalr {
+    /// The productions, the first one is the `$accept` of the root.
+    productions: Vec<Production>,
+    nullable: BTreeSet<String>,
+    first: HashMap<String, BTreeSet<String>>,
+    /// The level, from 1, and associativity of the operators with a
+    /// precedence.
+    precedences: HashMap<String, (usize, String)>,
+    /// The kernel items of each state.
+    states: Vec<Items>,
+    transitions: Vec<BTreeMap<String, usize>>,
+}
+
+fn assembler(arg: &str) -> Option<&str> {
+    arg.strip_prefix("as-")
+}
+
+impl Lalr {
+    /// The precedences go from the loosest to the tightest, with the
+    /// associativity, `left`, `right` or `nonassoc`, and the operators.
+    pub fn new(productions: Vec<Production>, precedences: Vec<(String, Vec<String>)>) -> Self {
+        let root = format!("as-{}", productions[0].name);
+        let mut all = vec![Production::new("$accept".to_string(), vec![root], 0)];
+        all.extend(productions);
+
+        let mut lalr = Lalr {
+            productions: all,
+            nullable: BTreeSet::new(),
+            first: HashMap::new(),
+            precedences: HashMap::new(),
+            states: vec![],
+            transitions: vec![],
+        };
+
+        for (level, (assoc, operators)) in precedences.into_iter().enumerate() {
+            for operator in operators {
+                lalr.precedences
+                    .insert(operator, (level + 1, assoc.clone()));
+            }
+        }
+
+        lalr.find_nullable();
+        lalr.find_first();
+        lalr.build();
+
+        lalr
+    }
+
+    fn find_nullable(&mut self) {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for p in &self.productions {
+                let nullable = p
+                    .args
+                    .iter()
+                    .all(|a| assembler(a).is_some_and(|a| self.nullable.contains(a)));
+
+                if nullable && !self.nullable.contains(&p.name) {
+                    self.nullable.insert(p.name.clone());
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    fn find_first(&mut self) {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for p in &self.productions {
+                let first = self.first_of(&p.args, &BTreeSet::new());
+                let set = self.first.entry(p.name.clone()).or_default();
+                let before = set.len();
+
+                set.extend(first);
+                changed |= set.len() > before;
+            }
+        }
+    }
+
+    /// The terminals that can start the arguments, with the lookaheads if
+    /// all of them can be empty.
+    fn first_of(&self, args: &[String], lookaheads: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut res = BTreeSet::new();
+
+        for arg in args {
+            match assembler(arg) {
+                Some(name) => {
+                    res.extend(self.first.get(name).into_iter().flatten().cloned());
+
+                    if !self.nullable.contains(name) {
+                        return res;
+                    }
+                }
+                None => {
+                    res.insert(arg.clone());
+                    return res;
+                }
+            }
+        }
+
+        res.extend(lookaheads.iter().cloned());
+        res
+    }
+
+    fn closure(&self, kernel: &Items) -> Items {
+        let mut items = kernel.clone();
+        let mut queue: Vec<Item> = items.keys().copied().collect();
+
+        while let Some((p, dot)) = queue.pop() {
+            let args = &self.productions[p].args;
+            let name = match args.get(dot).and_then(|a| assembler(a)) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let lookaheads = self.first_of(&args[dot + 1..], &items[&(p, dot)]);
+
+            for (q, production) in self.productions.iter().enumerate() {
+                if production.name != name {
+                    continue;
+                }
+
+                let new = !items.contains_key(&(q, 0));
+                let set = items.entry((q, 0)).or_default();
+                let before = set.len();
+
+                set.extend(lookaheads.iter().cloned());
+
+                if new || set.len() > before {
+                    queue.push((q, 0));
+                }
+            }
+        }
+
+        items
+    }
+
+    fn build(&mut self) {
+        let start = BTreeMap::from([((0, 0), BTreeSet::from([LR_END.to_string()]))]);
+        let mut cores: HashMap<Vec<Item>, usize> = HashMap::new();
+        let mut queue: VecDeque<usize> = VecDeque::from([0]);
+
+        cores.insert(vec![(0, 0)], 0);
+        self.states.push(start);
+        self.transitions.push(BTreeMap::new());
+
+        while let Some(s) = queue.pop_front() {
+            let mut gotos: BTreeMap<String, Items> = BTreeMap::new();
+
+            for ((p, dot), lookaheads) in self.closure(&self.states[s]) {
+                if let Some(next) = self.productions[p].args.get(dot) {
+                    gotos
+                        .entry(next.clone())
+                        .or_default()
+                        .insert((p, dot + 1), lookaheads);
+                }
+            }
+
+            for (symbol, kernel) in gotos {
+                let core: Vec<Item> = kernel.keys().copied().collect();
+
+                let t = match cores.get(&core) {
+                    Some(&t) => {
+                        let mut changed = false;
+
+                        for (item, lookaheads) in kernel {
+                            let set = self.states[t].entry(item).or_default();
+                            let before = set.len();
+
+                            set.extend(lookaheads);
+                            changed |= set.len() > before;
+                        }
+
+                        if changed && !queue.contains(&t) {
+                            queue.push_back(t);
+                        }
+
+                        t
+                    }
+                    None => {
+                        let t = self.states.len();
+
+                        cores.insert(core, t);
+                        self.states.push(kernel);
+                        self.transitions.push(BTreeMap::new());
+                        queue.push_back(t);
+
+                        t
+                    }
+                };
+
+                self.transitions[s].insert(symbol, t);
+            }
+        }
+    }
+
+    /// The precedence of a production is the one of its last operator with
+    /// a precedence, like in yacc.
+    fn production_precedence(&self, p: usize) -> Option<&(usize, String)> {
+        self.productions[p]
+            .args
+            .iter()
+            .rev()
+            .find_map(|a| self.precedences.get(a))
+    }
+
+    /// What to do when the production can be reduced and the lookahead
+    /// shifted, if both have a precedence: the tighter wins, and in the same
+    /// level the associativity chooses.
+    fn resolve(&self, p: usize, lookahead: &str) -> Option<Resolution> {
+        let (production, _) = self.production_precedence(p)?;
+        let (operator, assoc) = self.precedences.get(lookahead)?;
+
+        Some(match (production.cmp(operator), &assoc[..]) {
+            (Ordering::Greater, _) | (Ordering::Equal, "left") => Resolution::Reduce,
+            (Ordering::Less, _) | (Ordering::Equal, "right") => Resolution::Shift,
+            _ => Resolution::Error,
+        })
+    }
+
+    /// The action of each state for each lookahead, and the conflicts found.
+    /// A shift and a reduce are chosen with the precedences, when they
+    /// have one. Otherwise, a shift wins a conflict with a reduce, and the
+    /// first production wins between reduces, like in yacc.
+    pub fn actions(&self) -> (Vec<BTreeMap<String, Action>>, Vec<Conflict>) {
+        let mut table = vec![];
+        let mut conflicts = vec![];
+
+        for (s, kernel) in self.states.iter().enumerate() {
+            let mut cells: BTreeMap<String, (Vec<Item>, Vec<usize>)> = BTreeMap::new();
+
+            for ((p, dot), lookaheads) in self.closure(kernel) {
+                match self.productions[p].args.get(dot) {
+                    Some(next) if assembler(next).is_none() => {
+                        cells.entry(next.clone()).or_default().0.push((p, dot));
+                    }
+                    Some(_) => (),
+                    None => {
+                        for lookahead in lookaheads {
+                            cells.entry(lookahead).or_default().1.push(p);
+                        }
+                    }
+                }
+            }
+
+            let mut actions = BTreeMap::new();
+
+            for (lookahead, (shifts, reduces)) in cells {
+                let resolution = match (&shifts[..], &reduces[..]) {
+                    ([_, ..], [p]) => self.resolve(*p, &lookahead),
+                    _ => None,
+                };
+
+                match resolution {
+                    Some(Resolution::Shift) => {
+                        actions.insert(
+                            lookahead.clone(),
+                            Action::Shift(self.transitions[s][&lookahead]),
+                        );
+                        continue;
+                    }
+                    Some(Resolution::Reduce) => {
+                        actions.insert(lookahead, Action::Reduce(reduces[0] - 1));
+                        continue;
+                    }
+                    Some(Resolution::Error) => continue,
+                    None => (),
+                }
+
+                let action = match (shifts.is_empty(), reduces.first()) {
+                    (false, _) => Action::Shift(self.transitions[s][&lookahead]),
+                    (true, Some(0)) => Action::Accept,
+                    (true, Some(&p)) => Action::Reduce(p - 1),
+                    (true, None) => continue,
+                };
+
+                if reduces.len() > 1 || (!shifts.is_empty() && !reduces.is_empty()) {
+                    conflicts.push(Conflict {
+                        state: s,
+                        lookahead: lookahead.clone(),
+                        shifts,
+                        reduces,
+                    });
+                }
+
+                actions.insert(lookahead, action);
+            }
+
+            table.push(actions);
+        }
+
+        (table, conflicts)
+    }
+
+    /// The `lr` lines of the exaust, the reduces are the index of the `as`
+    /// line of the production.
+    pub fn mount(&self, actions: &[BTreeMap<String, Action>]) -> Vec<String> {
+        let mut res = vec![];
+
+        for (s, actions) in actions.iter().enumerate() {
+            let mut line = format!("lr {}", s);
+
+            for (terminal, action) in actions {
+                line += &match action {
+                    Action::Shift(t) => format!(" {}=s{}", terminal, t),
+                    Action::Reduce(p) => format!(" {}=r{}", terminal, p),
+                    Action::Accept => format!(" {}=a", terminal),
+                };
+            }
+
+            for (symbol, t) in &self.transitions[s] {
+                if assembler(symbol).is_some() {
+                    line += &format!(" {}=g{}", symbol, t);
+                }
+            }
+
+            res.push(line);
+        }
+
+        res
+    }
+
+    /// The Assemblers that expand to themselves without consuming any
+    /// token, like `<a> : <a>`, and if they do it directly.
+    pub fn cycles(&self) -> Vec<(String, bool, usize)> {
+        // The Assemblers each one can expand to without consuming a token.
+        let mut edges: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+
+        for p in &self.productions[1..] {
+            for (i, arg) in p.args.iter().enumerate() {
+                let name = match assembler(arg) {
+                    Some(name) => name,
+                    None => continue,
+                };
+
+                let mut others = p.args[..i].iter().chain(&p.args[i + 1..]);
+
+                if others.all(|a| assembler(a).is_some_and(|a| self.nullable.contains(a))) {
+                    edges.entry(&p.name).or_default().insert(name);
+                }
+            }
+        }
+
+        let mut res: Vec<(String, bool, usize)> = vec![];
+
+        for p in &self.productions[1..] {
+            if res.iter().any(|(name, ..)| *name == p.name) {
+                continue;
+            }
+
+            let direct = edges
+                .get(&p.name[..])
+                .is_some_and(|e| e.contains(&p.name[..]));
+            let mut seen: BTreeSet<&str> = BTreeSet::new();
+            let mut queue: Vec<&str> = edges
+                .get(&p.name[..])
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+
+            while let Some(name) = queue.pop() {
+                if seen.insert(name) {
+                    queue.extend(edges.get(name).into_iter().flatten());
+                }
+            }
+
+            if seen.contains(&p.name[..]) {
+                res.push((p.name.clone(), direct, p.line));
+            }
+        }
+
+        res
+    }
+
+    /// The item as written in TFLAC, with a • at the dot.
+    pub fn show(&self, (p, dot): Item) -> String {
+        let production = &self.productions[p];
+        let mut args: Vec<String> = production.args.iter().map(|a| show_symbol(a)).collect();
+
+        args.insert(dot, "•".to_string());
+
+        format!(
+            "<{}> : {} |{}|",
+            production.name,
+            args.join(" "),
+            production.line
+        )
+    }
+
+    /// The item of the production that is reduced.
+    pub fn reduced(&self, p: usize) -> Item {
+        (p, self.productions[p].args.len())
+    }
+}
+
+/// An argument of the exaust as written in TFLAC.
+pub fn show_symbol(symbol: &str) -> String {
+    match symbol.split_once('-') {
+        Some(("as", name)) => format!("<{}>", name),
+        Some(("se", name)) => format!("[{}]", name),
+        Some(("sy", name)) => format!(":{}:", name),
+        Some(("li", literal)) => literal.to_string(),
+        _ if symbol == LR_END => "the end of the tokens".to_string(),
+        _ => symbol.to_string(),
+    }
+}
@@ -17,6 +17,8 @@ fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
 }
 
 mod compiler;
+mod ebnf;
+mod lalr;
 use compiler::{Searcher, TflaCC};
 
 fn show_help() {
@@ -27,16 +29,16 @@ TFLA CC - TFLA Config \"Compiler\"
 tfla-cc <command> [arguments]
 
 General Commands:
-    --help | -h 
+    --help | -h
         Arguments: [command]
         Show this message or show the help of some command.
     --digest | -d
         Arguments: <path> [-a] [-w|-W] [output]
         Compile a TFLAC source and show the result.
-    --analyse | -a 
+    --analyse | -a
         Arguments: <path>
         Just analyse the source code to syntax and impossible cases.
-    --compile | -c 
+    --compile | -c
         Arguments: <path>
         Compile the passed source code with type .tflac to an .exaust.tfla file with same name.
         aka tfla-cc -d <path> -a -W"
@@ -87,51 +89,48 @@ fn main() {
                 write_in = true;
             } else if arg == "-W" {
                 write_same = true;
+            } else if input.is_empty() {
+                input = arg.to_string();
             } else {
-                if input == "".to_string() {
-                    input = arg.to_string();
-                } else {
-                    output = arg.to_string();
-                }
+                output = arg.to_string();
             }
         } else {
             input = arg.to_string();
         }
     }
     #[warn(unused_assignments)]
-    if input == "" {
+    if input.is_empty() {
         println!("Pass an input file.");
-        exit(0);
+        exit(1);
     }
 
     let content = fs::read_to_string(input.clone()).expect("Can't open the input file");
 
-    let mut c_searchers: Vec<(&str, &str)> = vec![];
-    c_searchers.push(Searcher::new("comment", r"^\-\-").transform());
-    c_searchers.push(Searcher::new("NEW_LINE", r"^(\r)?\n").transform());
-    c_searchers.push(Searcher::new("SPACE", r"^\s").transform());
-    c_searchers.push(Searcher::new("searcher", r"^\[(\w|_)+\]").transform());
-    c_searchers.push(Searcher::new("assembler", r"^<(\w|_)+>").transform());
-    c_searchers.push(Searcher::new("symbol", r"^\:(\w|_)+\:").transform());
-    c_searchers.push(Searcher::new("colon", r"^(:|:r|::=)").transform());
-    c_searchers.push(Searcher::new("pipe", r"^\|").transform());
-    c_searchers.push(Searcher::new("entity", r"^[^\s]*").transform());
+    let c_searchers: Vec<(&str, &str)> = vec![
+        Searcher::new("NEW_LINE", r"^(\r)?\n").transform(),
+        Searcher::new("SPACE", r"^\s").transform(),
+        Searcher::new("searcher", r"^\[(\w|_)+\]").transform(),
+        Searcher::new("assembler", r"^<(\w|_)+>").transform(),
+        Searcher::new("symbol", r"^\:(\w|_)+\:").transform(),
+        Searcher::new("colon", r"^(:|:r|::=)").transform(),
+        Searcher::new("pipe", r"^\|").transform(),
+        Searcher::new("directive", r"^\{(\w|_)+\}").transform(),
+        Searcher::new("precedence", r"^%(left|right|nonassoc)").transform(),
+        Searcher::new("literal", r#"^"[^"]*""#).transform(),
+        Searcher::new("group", r"^\(").transform(),
+        Searcher::new("group_end", r#"^\)(\?|("[^"]*")?(\.\.\.|\+|\d+))?"#).transform(),
+        Searcher::new("entity", r"^[^\s()|]+").transform(),
+    ];
 
     let cc: TflaCC = TflaCC::new(&content[..], c_searchers);
 
     if co == "-d" || co == "--digest" {
-        let res: String;
-
-        if analyse {
-            res = cc.analyse();
-        } else {
-            res = cc.digest();
-        }
+        let res = if analyse { cc.analyse() } else { cc.digest() };
 
         if write_in {
-            if output == "" {
+            if output.is_empty() {
                 println!("Pass an output file.");
-                exit(0);
+                exit(1);
             } else {
                 let _ = write_file(output, res);
             }
@@ -150,7 +149,7 @@ fn main() {
     if co == "-a" || co == "--analyse" {
         cc.analyse();
 
-        println!("\nEnd with 0 Error and 0 Warns");
+        println!("\nEnd with 0 Error and {} Warns", cc.warns());
     }
 
     if co == "-c" || co == "--compile" {
@@ -21,15 +21,13 @@ main() {
     
     git clone https://github.com/LucasOliveiraaa/TFLA.git 
 
-    cd TFLA/src/tfla_cc/
+    cd TFLA/
 
-    cargo build --release
+    cargo build --release -p tfla-cc
 
-    cd target/release/
+    sudo mv ./target/release/tfla-cc /usr/local/bin/
 
-    sudo mv ./tfla-cc /usr/local/bin/
-
-    cd ../..
+    cd ..
 
     sudo rm -r TFLA
 
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A typed value of a token, made by the [`Converter`] of its searcher.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TokenValue {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+}
+
+/// A user callback that converts the content of a token, the error message
+/// is reported as a lexing error.
+pub type ConvertFn = dyn Fn(&str) -> Result<TokenValue, String> + Send + Sync;
+
+/// How the content of a token is converted to a [`TokenValue`], see
+/// `TFLA::add_converter`.
+#[derive(Clone)]
+pub enum Converter {
+    /// A decimal integer, or a `0x`, `0o` or `0b` one, with an optional sign
+    /// and `_` separators.
+    Int,
+    Float,
+    /// A string between `"` or `'`, with its escapes replaced.
+    Str,
+    /// `true` or `false`.
+    Bool,
+    Custom(Arc<ConvertFn>),
+}
+
+impl fmt::Debug for Converter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Converter::Int => write!(f, "Int"),
+            Converter::Float => write!(f, "Float"),
+            Converter::Str => write!(f, "Str"),
+            Converter::Bool => write!(f, "Bool"),
+            Converter::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Converter {
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&str) -> Result<TokenValue, String> + Send + Sync + 'static,
+    {
+        Converter::Custom(Arc::new(f))
+    }
+
+    /// The converter named in TFLAC, like `=> int`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "int" => Some(Converter::Int),
+            "float" => Some(Converter::Float),
+            "string" => Some(Converter::Str),
+            "bool" => Some(Converter::Bool),
+            _ => None,
+        }
+    }
+
+    pub fn convert(&self, content: &str) -> Result<TokenValue, String> {
+        match self {
+            Converter::Int => int(content).map(TokenValue::Int),
+            Converter::Float => match content.replace('_', "").parse::<f64>() {
+                Ok(f) if f.is_finite() => Ok(TokenValue::Float(f)),
+                Ok(_) => Err(format!("The float {} overflows", content)),
+                Err(_) => Err(format!("Invalid float {}", content)),
+            },
+            Converter::Str => unescape(content).map(TokenValue::Str),
+            Converter::Bool => match content {
+                "true" => Ok(TokenValue::Bool(true)),
+                "false" => Ok(TokenValue::Bool(false)),
+                _ => Err(format!("Invalid bool {}", content)),
+            },
+            Converter::Custom(f) => f(content),
+        }
+    }
+}
+
+fn int(content: &str) -> Result<i64, String> {
+    let digits = content.replace('_', "");
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits.strip_prefix('+').unwrap_or(&digits)),
+    };
+
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x") | Some("0X") => (16, &digits[2..]),
+        Some("0o") | Some("0O") => (8, &digits[2..]),
+        Some("0b") | Some("0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+
+    // i64::from_str_radix takes a sign too, like the 5 of "+-5" or "0x-5".
+    if digits.starts_with(['+', '-']) {
+        return Err(format!("Invalid integer {}", content));
+    }
+
+    // The sign is parsed with the digits, so i64::MIN don't overflow.
+    let signed = if negative {
+        format!("-{}", digits)
+    } else {
+        digits.to_string()
+    };
+
+    i64::from_str_radix(&signed, radix).map_err(|e| match e.kind() {
+        std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => {
+            format!("The integer {} overflows", content)
+        }
+        _ => format!("Invalid integer {}", content),
+    })
+}
+
+fn unescape(content: &str) -> Result<String, String> {
+    let body = match content.chars().next() {
+        Some(q @ ('"' | '\'')) if content.len() > 1 && content.ends_with(q) => {
+            &content[1..content.len() - 1]
+        }
+        _ => content,
+    };
+
+    let mut res = String::with_capacity(body.len());
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"' | '\'')) => c,
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+
+                u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|b| hex.len() == 2 && b.is_ascii())
+                    .map(char::from)
+                    .ok_or_else(|| format!("Invalid escape \\x{} in {}", hex, content))?
+            }
+            Some('u') => {
+                let rest = chars.as_str();
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|r| r.split_once('}'))
+                    .map(|(hex, _)| hex)
+                    .ok_or_else(|| format!("Invalid escape \\u in {}", content))?;
+
+                let c = u32::from_str_radix(code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("Invalid escape \\u{{{}}} in {}", code, content))?;
+
+                chars = rest[code.len() + 2..].chars();
+                c
+            }
+            Some(c) => return Err(format!("Invalid escape \\{} in {}", c, content)),
+            None => return Err(format!("Unfinished escape in {}", content)),
+        };
+
+        res.push(escaped);
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,494 @@
+use tfla::{ASTGen, Assoc, AstError, Child, Exaust, ExaustError, LrAction, Node, Precedence, TFLA};
+
+fn searchers() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("Number", r"^\d+"),
+        ("Entity", r"^\w+"),
+        ("Operator", r"^[-+*/()]"),
+    ]
+}
+
+fn rules(node: &Node) -> Vec<String> {
+    let mut res = vec![node.rule.to_string()];
+
+    for child in &node.children {
+        match child {
+            Child::Node(node) => res.extend(rules(node)),
+            Child::Token(token) => res.push(token.content.to_string()),
+        }
+    }
+
+    res
+}
+
+#[test]
+fn ordered_choice() {
+    let assemblers = vec![
+        ("call", vec!["as-value", "li-\"(\"", "li-\")\""]),
+        ("call", vec!["as-value"]),
+        ("value", vec!["se-Number"]),
+        ("value", vec!["se-Entity"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("f ( )").unwrap();
+    let ast = tfla.assemble("call").unwrap();
+    assert_eq!(rules(&ast), ["call", "value", "f", "(", ")"]);
+
+    tfla.tokenize("42").unwrap();
+    let ast = tfla.assemble("call").unwrap();
+    assert_eq!(rules(&ast), ["call", "value", "42"]);
+}
+
+/// How many assemblers are expanded to assemble the sum `1 + 1 + ...` of `n`
+/// numbers, each one in `depth` parentheses.
+fn expansions(n: usize, depth: usize) -> usize {
+    // Without memoization each <expr> tries <term> twice, so the nested
+    // parentheses would take 2^depth tries, and the sum is as deep as the
+    // numbers.
+    let assemblers = vec![
+        ("expr", vec!["as-term", "li-\"+\"", "as-expr"]),
+        ("expr", vec!["as-term", "li-\"-\"", "as-expr"]),
+        ("expr", vec!["as-term"]),
+        ("term", vec!["li-\"(\"", "as-expr", "li-\")\""]),
+        ("term", vec!["se-Number"]),
+    ];
+
+    let number = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    let code = vec![number; n].join(" + ");
+
+    let mut tfla = TFLA::new(searchers(), assemblers.clone(), true);
+    tfla.tokenize(&code).unwrap();
+
+    let ast_gen = ASTGen::new(assemblers, vec![]);
+    let ast = ast_gen.generate(&tfla.tokens, "expr").unwrap();
+    assert_eq!(ast.end, n * (depth * 2 + 2) - 1);
+
+    ast_gen.expanded()
+}
+
+#[test]
+fn packrat_scales_linearly() {
+    // Each number and each parentheses expand the same assemblers, so the
+    // expansions grow by the same amount for the same tokens added.
+    let (one, two, ten) = (
+        expansions(1000, 2),
+        expansions(2000, 2),
+        expansions(10_000, 2),
+    );
+    assert_eq!(ten - two, (two - one) * 8);
+
+    let (one, two, four) = (expansions(1, 50), expansions(1, 100), expansions(1, 200));
+    assert_eq!(four - two, (two - one) * 2);
+}
+
+#[test]
+fn furthest_failure() {
+    let assemblers = vec![
+        ("sum", vec!["se-Number", "li-\"+\"", "se-Number"]),
+        ("sum", vec!["se-Number", "li-\"-\"", "se-Entity"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("1 + x").unwrap();
+    let err = tfla.assemble("sum").unwrap_err();
+    assert_eq!(
+        err,
+        AstError::Unexpected {
+            index: 2,
+            found: Some("x".to_string()),
+            expected: vec!["[Number]".to_string()],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Unexpected \"x\" at token 2, expected [Number]."
+    );
+
+    tfla.tokenize("1 * 2").unwrap();
+    let err = tfla.assemble("sum").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected \"*\" at token 1, expected \"+\" or \"-\"."
+    );
+
+    tfla.tokenize("1 + 2 3").unwrap();
+    let err = tfla.assemble("sum").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected \"3\" at token 3, expected end of tokens."
+    );
+
+    tfla.tokenize("1 +").unwrap();
+    let err = tfla.assemble("sum").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected end of tokens at token 2, expected [Number]."
+    );
+
+    assert_eq!(
+        tfla.assemble("product").unwrap_err(),
+        AstError::UnknownRule("product".to_string())
+    );
+}
+
+#[test]
+fn left_recursion_is_abandoned() {
+    let assemblers = vec![
+        ("list", vec!["as-list", "se-Entity"]),
+        ("list", vec!["se-Entity"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("a").unwrap();
+    let ast = tfla.assemble("list").unwrap();
+    assert_eq!(rules(&ast), ["list", "a"]);
+}
+
+// The output of `tfla-cc -d -a` for a left recursive expression grammar.
+const EXPR_EXAUST: &str = r#"se SPACE ^\s
+se Number ^\d+
+se Op ^[-+*()]
+as expr as-expr li-"+" as-term
+as expr as-term
+as term as-term li-"*" as-factor
+as term as-factor
+as factor li-"(" as-expr li-")"
+as factor se-Number
+lr 0 li-"("=s4 se-Number=s5 as-expr=g1 as-factor=g2 as-term=g3
+lr 1 $=a li-"+"=s6
+lr 2 $=r3 li-")"=r3 li-"*"=r3 li-"+"=r3
+lr 3 $=r1 li-")"=r1 li-"*"=s7 li-"+"=r1
+lr 4 li-"("=s4 se-Number=s5 as-expr=g8 as-factor=g2 as-term=g3
+lr 5 $=r5 li-")"=r5 li-"*"=r5 li-"+"=r5
+lr 6 li-"("=s4 se-Number=s5 as-factor=g2 as-term=g9
+lr 7 li-"("=s4 se-Number=s5 as-factor=g10
+lr 8 li-")"=s11 li-"+"=s6
+lr 9 $=r0 li-")"=r0 li-"*"=s7 li-"+"=r0
+lr 10 $=r2 li-")"=r2 li-"*"=r2 li-"+"=r2
+lr 11 $=r4 li-")"=r4 li-"*"=r4 li-"+"=r4"#;
+
+#[test]
+fn lr_table() {
+    let exaust = Exaust::from_source(EXPR_EXAUST.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    let table = tfla.lr_table.as_ref().unwrap();
+    assert_eq!(table.states.len(), 12);
+    assert_eq!(
+        table.states[1].actions,
+        [("$", LrAction::Accept), ("li-\"+\"", LrAction::Shift(6))]
+    );
+
+    tfla.tokenize("1 + 2 * 3").unwrap();
+    let ast = tfla.assemble_lr().unwrap();
+    assert_eq!(
+        rules(&ast),
+        [
+            "expr", "expr", "term", "factor", "1", "+", "term", "term", "factor", "2", "*",
+            "factor", "3"
+        ]
+    );
+    assert_eq!((ast.start, ast.end), (0, 5));
+
+    tfla.tokenize("(1 + ) 2").unwrap();
+    assert_eq!(
+        tfla.assemble_lr().unwrap_err().to_string(),
+        "Unexpected \")\" at token 3, expected \"(\" or [Number]."
+    );
+
+    tfla.tokenize("(1").unwrap();
+    assert_eq!(
+        tfla.assemble_lr().unwrap_err().to_string(),
+        "Unexpected end of tokens at token 2, expected \")\" or \"+\"."
+    );
+}
+
+#[test]
+fn lr_table_errors() {
+    let mut tfla = TFLA::new(searchers(), vec![], true);
+    assert_eq!(tfla.assemble_lr().unwrap_err(), AstError::NoLrTable);
+
+    tfla.tokenize("1").unwrap();
+    assert_eq!(tfla.assemble_lr().unwrap_err(), AstError::NoLrTable);
+
+    for source in [
+        "as a se-Number\nlr 0 se-Number=s1",
+        "as a se-Number\nlr 0 se-Number=s0 $=r1",
+        "as a se-Number\nlr 1 se-Number=s0",
+        "as a se-Number\nlr 0 se-Number=x0",
+        "as a se-Number\nlr 0 se-Number=",
+        "as a se-Number\nlr 0 se-Number=ç0",
+    ] {
+        let exaust = Exaust::from_source(source.to_string());
+
+        assert!(matches!(
+            TFLA::from_exaust(&exaust, true),
+            Err(ExaustError::Syntax { line: 2, .. })
+        ));
+    }
+
+    // The reduce pops more than the stack has.
+    let exaust = Exaust::from_source(
+        "se Number ^\\d+\nas a se-Number se-Number\nlr 0 se-Number=r0".to_string(),
+    );
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    tfla.tokenize("1").unwrap();
+    assert_eq!(tfla.assemble_lr().unwrap_err(), AstError::NoLrTable);
+}
+
+fn ambiguous() -> Vec<(&'static str, Vec<&'static str>)> {
+    vec![
+        ("expr", vec!["as-expr", "li-\"+\"", "as-expr"]),
+        ("expr", vec!["as-expr", "li-\"*\"", "as-expr"]),
+        ("expr", vec!["se-Number"]),
+    ]
+}
+
+#[test]
+fn forest_of_ambiguous_grammar() {
+    let mut tfla = TFLA::new(searchers(), ambiguous(), true);
+
+    tfla.tokenize("1 + 2 * 3").unwrap();
+    let forest = tfla.assemble_forest("expr").unwrap();
+
+    assert!(forest.is_ambiguous());
+    assert_eq!(forest.count(), 2);
+
+    let trees: Vec<Vec<String>> = forest.trees().iter().map(rules).collect();
+    assert_eq!(
+        trees,
+        [
+            ["expr", "expr", "1", "+", "expr", "expr", "2", "*", "expr", "3"],
+            ["expr", "expr", "expr", "1", "+", "expr", "2", "*", "expr", "3"],
+        ]
+    );
+
+    // The trees share the nodes of the numbers.
+    assert_eq!(forest.nodes.len(), 6);
+
+    // "*" binds tighter than "+", so the root is the "+".
+    let tree = forest.disambiguate(|node| {
+        node.alternatives
+            .iter()
+            .position(|packed| packed.assembler == 0)
+            .unwrap_or(0)
+    });
+    assert_eq!(rules(&tree), trees[0]);
+
+    // The number of trees grows with the Catalan numbers.
+    tfla.tokenize("1 + 2 + 3 + 4 + 5 + 6").unwrap();
+    assert_eq!(tfla.assemble_forest("expr").unwrap().count(), 42);
+
+    // The nodes are counted once, so counting more trees than a usize can
+    // have takes as long as the forest.
+    let sum = vec!["1"; 60].join(" + ");
+    let mut long = TFLA::new(searchers(), ambiguous(), true);
+    long.tokenize(&sum).unwrap();
+    let forest = long.assemble_forest("expr").unwrap();
+    assert!(forest.is_ambiguous());
+    assert_eq!(forest.count(), usize::MAX);
+
+    tfla.tokenize("7").unwrap();
+    let forest = tfla.assemble_forest("expr").unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(rules(&forest.tree()), ["expr", "7"]);
+}
+
+#[test]
+fn forest_with_empty_and_symbols() {
+    let assemblers = vec![
+        ("list", vec!["as-items", "sy-eof"]),
+        ("items", vec!["as-items", "se-Entity"]),
+        ("items", vec!["li-ε"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("a b c").unwrap();
+    let forest = tfla.assemble_forest("list").unwrap();
+    assert_eq!(forest.count(), 1);
+    assert_eq!(
+        rules(&forest.tree()),
+        ["list", "items", "items", "items", "items", "a", "b", "c"]
+    );
+
+    tfla.tokenize("").unwrap();
+    assert_eq!(
+        rules(&tfla.assemble_forest("list").unwrap().tree()),
+        ["list", "items"]
+    );
+
+    tfla.tokenize("a 1").unwrap();
+    assert_eq!(
+        tfla.assemble_forest("list").unwrap_err().to_string(),
+        "Unexpected \"1\" at token 1, expected :eof: or [Entity]."
+    );
+}
+
+#[test]
+fn forest_of_cyclic_grammar() {
+    let assemblers = vec![
+        ("a", vec!["as-b"]),
+        ("a", vec!["se-Number"]),
+        ("b", vec!["as-a"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("7").unwrap();
+    let forest = tfla.assemble_forest("a").unwrap();
+    assert_eq!(forest.count(), 1);
+    assert_eq!(rules(&forest.tree()), ["a", "7"]);
+}
+
+/// The tree as an s-expression, with the operators first.
+fn sexp(node: &Node) -> String {
+    let children: Vec<String> = node
+        .children
+        .iter()
+        .map(|child| match child {
+            Child::Node(node) => sexp(node),
+            Child::Token(token) => token.content.to_string(),
+        })
+        .collect();
+
+    match &children[..] {
+        [lhs, op, rhs] if lhs != "(" => format!("({} {} {})", op, lhs, rhs),
+        [op, operand] => format!("({} {})", op, operand),
+        [_, inner, _] => inner.clone(),
+        [value] => value.clone(),
+        _ => children.join(" "),
+    }
+}
+
+#[test]
+fn pratt_precedence() {
+    let assemblers = vec![
+        ("e", vec!["as-e", "li-\"<\"", "as-e"]),
+        ("e", vec!["as-e", "li-\"+\"", "as-e"]),
+        ("e", vec!["as-e", "li-\"-\"", "as-e"]),
+        ("e", vec!["as-e", "li-\"*\"", "as-e"]),
+        ("e", vec!["as-e", "li-\"^\"", "as-e"]),
+        ("e", vec!["li-\"-\"", "as-e"]),
+        ("e", vec!["li-\"(\"", "as-e", "li-\")\""]),
+        ("e", vec!["se-Number"]),
+    ];
+
+    let mut searchers = searchers();
+    searchers.push(("Other", r"^[<^]"));
+
+    let mut tfla = TFLA::new(searchers, assemblers, true);
+    tfla.precedences = vec![
+        Precedence::new(Assoc::NonAssoc, &["li-\"<\""]),
+        Precedence::new(Assoc::Left, &["li-\"+\"", "li-\"-\""]),
+        Precedence::new(Assoc::Left, &["li-\"*\""]),
+        Precedence::new(Assoc::Right, &["li-\"^\""]),
+    ];
+
+    for (code, expected) in [
+        ("1 - 2 - 3", "(- (- 1 2) 3)"),
+        ("1 + 2 * 3", "(+ 1 (* 2 3))"),
+        ("2 ^ 3 ^ 4", "(^ 2 (^ 3 4))"),
+        ("(1 + 2) * 3", "(* (+ 1 2) 3)"),
+        ("- 1 * 2 + 3", "(+ (- (* 1 2)) 3)"),
+        (
+            "1 - 2 - 3 * 4 ^ 5 ^ 6 < - 7",
+            "(< (- (- 1 2) (* 3 (^ 4 (^ 5 6)))) (- 7))",
+        ),
+    ] {
+        tfla.tokenize(code).unwrap();
+        assert_eq!(sexp(&tfla.assemble("e").unwrap()), expected, "{}", code);
+    }
+
+    tfla.tokenize("1 < 2 < 3").unwrap();
+    assert_eq!(
+        tfla.assemble("e").unwrap_err().to_string(),
+        "Unexpected \"<\" at token 3, expected end of tokens."
+    );
+
+    tfla.tokenize("1 + * 2").unwrap();
+    assert_eq!(
+        tfla.assemble("e").unwrap_err().to_string(),
+        "Unexpected \"*\" at token 2, expected \"-\" or \"(\" or [Number]."
+    );
+}
+
+#[test]
+fn precedence_in_exaust() {
+    let source = r#"se SPACE ^\s
+se Number ^\d+
+se Op ^[-+*]
+pr left li-"+" li-"-"
+pr right li-"*"
+as e as-e li-"+" as-e
+as e as-e li-"-" as-e
+as e as-e li-"*" as-e
+as e se-Number"#;
+    let exaust = Exaust::from_source(source.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    assert_eq!(
+        tfla.precedences,
+        [
+            Precedence::new(Assoc::Left, &["li-\"+\"", "li-\"-\""]),
+            Precedence::new(Assoc::Right, &["li-\"*\""]),
+        ]
+    );
+
+    tfla.tokenize("1 * 2 * 3 - 4 + 5").unwrap();
+    assert_eq!(
+        sexp(&tfla.assemble("e").unwrap()),
+        "(+ (- (* 1 (* 2 3)) 4) 5)"
+    );
+
+    for source in ["pr up li-\"+\"", "pr left", "pr left Number"] {
+        let exaust = Exaust::from_source(source.to_string());
+
+        assert!(matches!(
+            TFLA::from_exaust(&exaust, true),
+            Err(ExaustError::Syntax { line: 1, .. })
+        ));
+    }
+}
+
+#[test]
+fn groups_are_flattened() {
+    // <call> : [Entity] "(" (<value>)"+"... ")", as mounted by tfla-cc.
+    let assemblers = vec![
+        (
+            "call",
+            vec!["se-Entity", "li-\"(\"", "as-call#1.1", "li-\")\""],
+        ),
+        ("call#1.2", vec!["as-value", "li-\"+\"", "as-call#1.2"]),
+        ("call#1.2", vec!["as-value"]),
+        ("call#1.1", vec!["as-call#1.2"]),
+        ("call#1.1", vec!["li-ε"]),
+        ("value", vec!["as-call"]),
+        ("value", vec!["se-Number"]),
+    ];
+
+    let mut tfla = TFLA::new(searchers(), assemblers, true);
+
+    tfla.tokenize("f(1 + g() + 2)").unwrap();
+    let expected = [
+        "call", "f", "(", "value", "1", "+", "value", "call", "g", "(", ")", "+", "value", "2", ")",
+    ];
+    assert_eq!(rules(&tfla.assemble("call").unwrap()), expected);
+    assert_eq!(
+        rules(&tfla.assemble_forest("call").unwrap().tree()),
+        expected
+    );
+
+    tfla.tokenize("f(1 +)").unwrap();
+    assert_eq!(
+        tfla.assemble("call").unwrap_err().to_string(),
+        "Unexpected \")\" at token 4, expected [Entity] or [Number]."
+    );
+}
@@ -0,0 +1,67 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::Write;
+use std::process::Command;
+
+fn tfla(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_tfla"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The lines of the nodes in the printed AST, without the tokens.
+fn nodes(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| line.trim_start().starts_with('<'))
+        .collect()
+}
+
+#[test]
+fn demo_assembles_lines() {
+    assert_eq!(
+        nodes(&tfla(&[])),
+        [
+            "<lines> 0..10",
+            "  <line> 0..1",
+            "  <lines> 2..10",
+            "    <line> 2..4",
+            "      <line> 3..4",
+            "    <lines> 5..10",
+            "      <line> 5..7",
+            "        <line> 6..7",
+            "      <lines> 8..10",
+            "        <line> 8..10",
+            "          <line> 9..10",
+        ]
+    );
+}
+
+#[test]
+fn runs_a_compiled_exaust() {
+    let dir = std::env::temp_dir().join(format!("tfla-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(
+            b"se SPACE ^\\s\nse Entity ^\\w+\nas words se-Entity as-words\nas words se-Entity",
+        )
+        .unwrap();
+    let exaust = dir.join("words.exaust.tfla");
+    fs::write(&exaust, encoder.finish().unwrap()).unwrap();
+
+    let input = dir.join("words.txt");
+    fs::write(&input, "a b").unwrap();
+
+    let output = tfla(&[exaust.to_str().unwrap(), input.to_str().unwrap()]);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.contains("[Entity] \"a\""), "{}", output);
+    assert!(output.contains("[Entity] \"b\""), "{}", output);
+    assert_eq!(nodes(&output), ["<words> 0..2", "  <words> 1..2"]);
+}
@@ -0,0 +1,113 @@
+use tfla::{Child, Exaust, MatchPolicy, TokenizeErrorKind, ERROR, TFLA};
+
+const WORDS_EXAUST: &str = r"se NEW_LINE ^(\r)?\n
+se SPACE ^\s
+se Entity ^\w+
+as words se-Entity as-words
+as words se-Entity";
+
+#[test]
+fn library_tokenizes_and_assembles() {
+    let exaust = Exaust::from_source(WORDS_EXAUST.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    tfla.tokenize("a b").unwrap();
+    let contents: Vec<&str> = tfla.tokens.iter().map(|t| t.content).collect();
+    assert_eq!(contents, ["a", "b"]);
+
+    let ast = tfla.assemble("words").unwrap();
+    assert_eq!((ast.rule, ast.start, ast.end), ("words", 0, 2));
+    assert!(
+        matches!(&ast.children[..], [Child::Token(a), Child::Node(rest)]
+        if a.content == "a" && rest.rule == "words")
+    );
+}
+
+#[test]
+fn unmatched_code_is_an_error() {
+    let exaust = Exaust::from_source(WORDS_EXAUST.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    let error = tfla.tokenize("ab\ncd ?x y\nz").unwrap_err();
+    assert_eq!(
+        (error.line, error.offset, error.snippet.as_str()),
+        (2, 6, "?x y")
+    );
+}
+
+#[test]
+fn recovery_emits_error_tokens() {
+    let exaust = Exaust::from_source(WORDS_EXAUST.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+    tfla.recover_errors = true;
+
+    let tokens = tfla.tokenize("ab ?! cd\n$").unwrap();
+    let kinds: Vec<(&str, &str)> = tokens.iter().map(|t| (t.ty, t.content)).collect();
+    assert_eq!(
+        kinds,
+        [
+            ("Entity", "ab"),
+            (ERROR, "?!"),
+            ("Entity", "cd"),
+            ("NEW_LINE", "\n"),
+            (ERROR, "$")
+        ]
+    );
+
+    let errors: Vec<(usize, usize, &str)> = tfla
+        .errors
+        .iter()
+        .map(|e| (e.line, e.offset, e.snippet.as_str()))
+        .collect();
+    assert_eq!(errors, [(1, 3, "?! cd"), (2, 9, "$")]);
+}
+
+#[test]
+fn positions_are_exact() {
+    let exaust = Exaust::from_source(WORDS_EXAUST.to_string());
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+
+    let source = "ab  cd\nef";
+    let tokens = tfla.tokenize(source).unwrap();
+    let positions: Vec<(usize, usize, usize, usize)> = tokens
+        .iter()
+        .map(|t| (t.line, t.start, t.end, t.offset))
+        .collect();
+    assert_eq!(
+        positions,
+        [(1, 1, 3, 0), (1, 5, 7, 4), (1, 7, 1, 6), (2, 1, 3, 7)]
+    );
+
+    for token in &tokens {
+        assert_eq!(
+            &source[token.offset..token.offset + token.content.len()],
+            token.content
+        );
+    }
+
+    let mut tfla = TFLA::from_exaust(&exaust, true).unwrap();
+    let error = tfla.tokenize("ab\ncd ?x").unwrap_err();
+    assert_eq!((error.line, error.column, error.offset), (2, 4, 6));
+}
+
+#[test]
+fn empty_matches_are_no_match() {
+    let searchers = vec![("SPACE", r"^\s"), ("Digits", r"^\d*"), ("Word", r"^[a-z]+")];
+    let mut tfla = TFLA::new(searchers.clone(), vec![], true);
+
+    let tokens = tfla.tokenize("12 ab").unwrap();
+    let kinds: Vec<(&str, &str)> = tokens.iter().map(|t| (t.ty, t.content)).collect();
+    assert_eq!(kinds, [("Digits", "12"), ("Word", "ab")]);
+
+    let error = tfla.tokenize("12 ?").unwrap_err();
+    assert_eq!((error.kind, error.offset), (TokenizeErrorKind::NoMatch, 3));
+
+    tfla.recover_errors = true;
+    let tokens = tfla.tokenize("1?!2").unwrap();
+    let kinds: Vec<(&str, &str)> = tokens.iter().map(|t| (t.ty, t.content)).collect();
+    assert_eq!(kinds, [("Digits", "1"), (ERROR, "?!"), ("Digits", "2")]);
+
+    tfla.match_policy = MatchPolicy::Longest;
+    let tokens = tfla.tokenize("ab").unwrap();
+    assert_eq!((tokens[0].ty, tokens[0].content), ("Word", "ab"));
+}
@@ -0,0 +1,61 @@
+use tfla::{Converter, OwnedToken, Span, TokenKind, TokenValue, TFLA};
+
+fn tokenizer() -> TFLA<'static> {
+    let searchers = vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("Number", r"^(?P<digits>\d+)"),
+        ("Entity", r"^\w+"),
+    ];
+
+    let mut tfla = TFLA::new(searchers, vec![], true);
+    tfla.add_converter("Number", Converter::Int);
+    tfla
+}
+
+#[test]
+fn json_round_trip() {
+    let mut tfla = tokenizer();
+    let tokens = tfla.tokenize("let a 42\nção").unwrap();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let owned: Vec<OwnedToken> = serde_json::from_str(&json).unwrap();
+
+    let expected: Vec<OwnedToken> = tokens.into_iter().map(OwnedToken::from).collect();
+    assert_eq!(owned, expected);
+    assert_eq!(owned[2].value, Some(TokenValue::Int(42)));
+    assert_eq!(owned[2].group("digits"), Some("42"));
+
+    let number: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(number[2]["ty"], "Number");
+    assert_eq!(number[2]["value"]["Int"], 42);
+}
+
+#[test]
+fn bincode_round_trip() {
+    let mut tfla = tokenizer();
+    let tokens = tfla.tokenize("let a 42\nção").unwrap();
+
+    let bin = bincode::serialize(&tokens).unwrap();
+    let owned: Vec<OwnedToken> = bincode::deserialize(&bin).unwrap();
+
+    let expected: Vec<OwnedToken> = tokens.into_iter().map(OwnedToken::from).collect();
+    assert_eq!(owned, expected);
+}
+
+#[test]
+fn span_and_kind_round_trip() {
+    let span = Span {
+        start: 3,
+        end: 7,
+        line: 2,
+        column: 1,
+    };
+
+    let json = serde_json::to_string(&(span, TokenKind::DEDENT)).unwrap();
+    assert_eq!(json, r#"[{"start":3,"end":7,"line":2,"column":1},2]"#);
+    assert_eq!(
+        serde_json::from_str::<(Span, TokenKind)>(&json).unwrap(),
+        (span, TokenKind::DEDENT)
+    );
+}
@@ -0,0 +1,116 @@
+use std::io::{BufReader, Cursor};
+
+use tfla::{Offside, OwnedToken, StreamError, TFLA};
+
+fn searchers() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("String", r#"^"[^"]*""#),
+        ("GreaterEqual", r"^>="),
+        ("Greater", r"^>"),
+        ("Entity", r"^\w+"),
+    ]
+}
+
+const CODE: &str = "let a = \"multi\nline ção\" >\n>= 42\nend";
+
+fn stream(
+    searchers: Vec<(&'static str, &'static str)>,
+    code: &str,
+) -> Vec<Result<OwnedToken, StreamError>> {
+    let tfla = TFLA::new(searchers, vec![], true);
+    let reader = BufReader::with_capacity(1, Cursor::new(code.as_bytes().to_vec()));
+
+    tfla.stream(reader).collect()
+}
+
+#[test]
+fn stream_matches_tokenize() {
+    let mut searchers = searchers();
+    searchers.push(("Equal", r"^="));
+
+    let mut tfla = TFLA::new(searchers.clone(), vec![], true);
+    let expected: Vec<OwnedToken> = tfla
+        .tokenize(CODE)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_owned())
+        .collect();
+
+    let tokens: Vec<OwnedToken> = stream(searchers, CODE)
+        .into_iter()
+        .map(|t| t.unwrap())
+        .collect();
+
+    assert_eq!(tokens, expected);
+    assert_eq!(tokens[3].content, "\"multi\nline ção\"");
+    assert_eq!((tokens[3].line, tokens[3].start, tokens[3].end), (1, 9, 10));
+}
+
+#[test]
+fn stream_waits_for_longer_searchers() {
+    let searchers = vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("Block", r"^\{[^}]*\}"),
+        ("LBrace", r"^\{"),
+        ("RBrace", r"^\}"),
+        ("Entity", r"^\w+"),
+    ];
+    let code = "{ x\n}";
+
+    let mut tfla = TFLA::new(searchers.clone(), vec![], true);
+    let expected: Vec<OwnedToken> = tfla
+        .tokenize(code)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_owned())
+        .collect();
+
+    let tokens: Vec<OwnedToken> = stream(searchers, code)
+        .into_iter()
+        .map(|t| t.unwrap())
+        .collect();
+
+    assert_eq!(tokens, expected);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].ty, "Block");
+}
+
+#[test]
+fn stream_reports_errors() {
+    let tokens = stream(searchers(), "abc\nd = e");
+
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(tokens[2].as_ref().unwrap().content, "d");
+
+    match &tokens[3] {
+        Err(StreamError::Tokenize(e)) => {
+            assert_eq!((e.line, e.column, e.offset), (2, 3, 6));
+            assert_eq!(e.snippet, "= e");
+        }
+        other => panic!("expected a tokenize error, found {:?}", other),
+    }
+}
+
+#[test]
+fn stream_offside_rule() {
+    let code = "a\n  b\n    c\n\n  d\n";
+
+    let mut tfla = TFLA::new(searchers(), vec![], true);
+    tfla.offside = Some(Offside::default());
+
+    let expected: Vec<OwnedToken> = tfla
+        .tokenize(code)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_owned())
+        .collect();
+
+    let reader = BufReader::with_capacity(1, Cursor::new(code.as_bytes().to_vec()));
+    let tokens: Vec<OwnedToken> = tfla.stream(reader).map(|t| t.unwrap()).collect();
+
+    assert_eq!(tokens, expected);
+    assert_eq!(tokens.iter().filter(|t| t.ty == "DEDENT").count(), 2);
+}
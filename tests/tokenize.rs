@@ -0,0 +1,545 @@
+use tfla::comment::{BLOCK_COMMENT, LINE_COMMENT};
+use tfla::{
+    Comment, Converter, Exaust, ExaustError, MatchPolicy, ModeAction, Offside, OwnedToken,
+    SearcherError, Token, TokenKind, TokenValue, TokenizeErrorKind, DEFAULT_MODE, TFLA,
+};
+
+fn searchers() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("NEW_LINE", r"^(\r)?\n"),
+        ("SPACE", r"^\s"),
+        ("Entity", r"^\w+"),
+        ("Other", r"^[^\w\s]"),
+    ]
+}
+
+fn commented() -> TFLA<'static> {
+    let mut tfla = TFLA::new(searchers(), vec![], true);
+    tfla.add_comment(Comment::line(LINE_COMMENT, r"//"))
+        .unwrap();
+    tfla.add_comment(Comment::block(BLOCK_COMMENT, r"/\*", r"\*/"))
+        .unwrap();
+
+    tfla
+}
+
+fn kinds<'a>(tokens: &[Token<'a>]) -> Vec<(&'a str, &'a str)> {
+    tokens.iter().map(|t| (t.ty, t.content)).collect()
+}
+
+#[test]
+fn line_comment_with_accents() {
+    let mut tfla = commented();
+    let tokens = tfla
+        .tokenize("// comentário com acentuação é ñ\nfim")
+        .unwrap();
+
+    assert_eq!(kinds(&tokens), vec![("NEW_LINE", "\n"), ("Entity", "fim")]);
+    assert_eq!((tokens[1].line, tokens[1].start), (2, 1));
+    assert_eq!(tokens[1].offset, "// comentário com acentuação é ñ\n".len());
+}
+
+#[test]
+fn block_comment_with_multibyte_chars() {
+    let mut tfla = commented();
+    let source = "/* ação 日本語 🦀 */ x";
+    let tokens = tfla.tokenize(source).unwrap();
+
+    assert_eq!(kinds(&tokens), vec![("Entity", "x")]);
+    assert_eq!(tokens[0].start, source.chars().count());
+    assert_eq!(&source[tokens[0].range()], "x");
+}
+
+#[test]
+fn keep_comments() {
+    let mut tfla = commented();
+    tfla.keep_comments = true;
+
+    let tokens = tfla.tokenize("a // b */\r\n/* c\n// d */ e").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![
+            ("Entity", "a"),
+            (LINE_COMMENT, "// b */"),
+            ("NEW_LINE", "\r\n"),
+            (BLOCK_COMMENT, "/* c\n// d */"),
+            ("Entity", "e")
+        ]
+    );
+    assert_eq!((tokens[4].line, tokens[4].start), (3, 9));
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let mut tfla = commented();
+    let error = tfla.tokenize("a\n b /* c\nd").unwrap_err();
+
+    assert_eq!(error.kind, TokenizeErrorKind::UnterminatedComment);
+    assert_eq!((error.line, error.column, error.offset), (2, 4, 5));
+    assert_eq!(error.snippet, "/* c");
+}
+
+#[test]
+fn nested_block_comment() {
+    let mut tfla = TFLA::new(searchers(), vec![], true);
+    tfla.add_comment(Comment::nested(BLOCK_COMMENT, r"/\*", r"\*/"))
+        .unwrap();
+
+    let tokens = tfla.tokenize("/* a /* b */ c */ d /**/ e").unwrap();
+    assert_eq!(kinds(&tokens), vec![("Entity", "d"), ("Entity", "e")]);
+
+    let error = tfla.tokenize("x /* a /* b */ c").unwrap_err();
+    assert_eq!(error.kind, TokenizeErrorKind::UnterminatedComment);
+    assert_eq!(error.column, 3);
+}
+
+#[test]
+fn columns_count_chars() {
+    let mut tfla = TFLA::new(searchers(), vec![], true);
+    let tokens = tfla.tokenize("ação x\nçé 🦀").unwrap();
+
+    let positions: Vec<(usize, usize, usize, usize)> = tokens
+        .iter()
+        .map(|t| (t.line, t.start, t.end, t.offset))
+        .collect();
+
+    assert_eq!(
+        positions,
+        vec![
+            (1, 1, 5, 0),
+            (1, 6, 7, 7),
+            (1, 7, 1, 8),
+            (2, 1, 3, 9),
+            (2, 4, 5, 14)
+        ]
+    );
+}
+
+#[test]
+fn recovered_error_with_multibyte_char() {
+    let mut tfla = TFLA::new(vec![("SPACE", r"^\s"), ("Word", r"^[a-z]+")], vec![], true);
+    tfla.recover_errors = true;
+
+    let tokens = tfla.tokenize("abc €ü def").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![("Word", "abc"), ("ERROR", "€ü"), ("Word", "def")]
+    );
+    assert_eq!(tfla.errors.len(), 1);
+    assert_eq!((tfla.errors[0].column, tfla.errors[0].offset), (5, 4));
+    assert_eq!(tfla.errors[0].snippet, "€ü def");
+}
+
+#[test]
+fn error_snippet_with_multibyte_char() {
+    let mut tfla = TFLA::new(vec![("SPACE", r"^\s"), ("Word", r"^[a-z]+")], vec![], true);
+    let error = tfla.tokenize("abc\nd ção").unwrap_err();
+
+    assert_eq!((error.line, error.column, error.offset), (2, 3, 6));
+    assert_eq!(error.snippet, "ção");
+}
+
+fn operators() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("SPACE", r"^\s"),
+        ("Greater", r"^>"),
+        ("GreaterEqual", r"^>="),
+        ("Entity", r"^\w+"),
+        ("If", r"^if"),
+        ("Equal", r"^="),
+    ]
+}
+
+#[test]
+fn first_match_policy() {
+    let mut tfla = TFLA::new(operators(), vec![], true);
+    let tokens = tfla.tokenize("a >= if").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![
+            ("Entity", "a"),
+            ("Greater", ">"),
+            ("Equal", "="),
+            ("Entity", "if")
+        ]
+    );
+}
+
+#[test]
+fn longest_match_policy() {
+    let mut tfla = TFLA::new(operators(), vec![], true);
+    tfla.match_policy = MatchPolicy::Longest;
+
+    let tokens = tfla.tokenize("a >= if iff").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![
+            ("Entity", "a"),
+            ("GreaterEqual", ">="),
+            ("Entity", "if"),
+            ("Entity", "iff")
+        ]
+    );
+}
+
+#[test]
+fn priority_match_policy() {
+    let mut tfla = TFLA::new(operators(), vec![], true);
+    tfla.match_policy = MatchPolicy::Priority;
+    tfla.priorities.insert("If", 1);
+
+    let tokens = tfla.tokenize("a >= if").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![("Entity", "a"), ("GreaterEqual", ">="), ("If", "if")]
+    );
+}
+
+#[test]
+fn tokens_can_be_reused_and_shared() {
+    let tfla = TFLA::new(searchers(), vec![], true);
+
+    let lines: Vec<Vec<(usize, usize)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = ["a b\nc", "d\ne f"]
+            .into_iter()
+            .map(|code| {
+                let tfla = &tfla;
+                scope.spawn(move || {
+                    tfla.tokens(code)
+                        .map(|t| t.map(|t| (t.line, t.start)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    assert_eq!(lines[0], vec![(1, 1), (1, 3), (1, 4), (2, 1)]);
+    assert_eq!(lines[1], vec![(1, 1), (1, 2), (2, 1), (2, 3)]);
+
+    let first: Vec<_> = tfla.tokens("x\ny").map(|t| t.unwrap().line).collect();
+    let second: Vec<_> = tfla.tokens("x\ny").map(|t| t.unwrap().line).collect();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn tokens_are_lazy() {
+    let tfla = TFLA::new(vec![("Word", r"^[a-z]+"), ("SPACE", r"^\s")], vec![], true);
+    let mut tokens = tfla.tokens("abc def 123");
+
+    assert_eq!(tokens.next().unwrap().unwrap().content, "abc");
+    assert_eq!(tokens.next().unwrap().unwrap().content, "def");
+    assert_eq!(tokens.next().unwrap().unwrap_err().offset, 8);
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn lexer_modes() {
+    let mut tfla = TFLA::new(
+        vec![
+            ("Quote", r#"^""#),
+            ("SPACE", r"^\s"),
+            ("Entity", r"^\w+"),
+            ("Text", r#"^[^"$]+"#),
+            ("Interpolation", r"^\$\{"),
+            ("Close", r"^\}"),
+            ("EndQuote", r#"^""#),
+        ],
+        vec![],
+        true,
+    );
+    tfla.add_mode(DEFAULT_MODE, &["Quote", "SPACE", "Entity", "Close"])
+        .unwrap();
+    tfla.add_mode("string", &["Text", "Interpolation", "EndQuote"])
+        .unwrap();
+    tfla.add_action("Quote", ModeAction::Push("string"));
+    tfla.add_action("Interpolation", ModeAction::Push(DEFAULT_MODE));
+    tfla.add_action("Close", ModeAction::Pop);
+    tfla.add_action("EndQuote", ModeAction::Pop);
+
+    let tokens = tfla.tokenize(r#"say "hi ${name} !" x"#).unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![
+            ("Entity", "say"),
+            ("Quote", "\""),
+            ("Text", "hi "),
+            ("Interpolation", "${"),
+            ("Entity", "name"),
+            ("Close", "}"),
+            ("Text", " !"),
+            ("EndQuote", "\""),
+            ("Entity", "x")
+        ]
+    );
+
+    assert_eq!(tfla.add_mode("other", &["Missing"]), Err("Missing"));
+}
+
+#[test]
+fn named_capture_groups() {
+    let mut tfla = TFLA::new(
+        vec![
+            ("SPACE", r"^\s"),
+            ("String", r#"^"(?P<body>[^"]*)""#),
+            (
+                "Number",
+                r"^(?:0(?P<radix>[xb]))?(?P<digits>[0-9a-f]+)(?P<suffix>u8|i32)?",
+            ),
+        ],
+        vec![],
+        true,
+    );
+
+    let tokens = tfla.tokenize(r#""ção" 0xff 42i32"#).unwrap();
+
+    assert_eq!(tokens[0].group("body"), Some("ção"));
+    assert_eq!(tokens[1].group("radix"), Some("x"));
+    assert_eq!(tokens[1].group("digits"), Some("ff"));
+    assert_eq!(tokens[1].group("suffix"), None);
+    assert_eq!(tokens[2].group("radix"), None);
+    assert_eq!(tokens[2].group("suffix"), Some("i32"));
+    assert_eq!(tokens[2].group("other"), None);
+
+    let owned = tokens[1].clone().into_owned();
+    assert_eq!(owned.group("digits"), Some("ff"));
+}
+
+#[test]
+fn capture_groups_see_the_code_after_the_token() {
+    let mut tfla = TFLA::new(
+        vec![
+            ("SPACE", r"^\s"),
+            ("Head", r"^(?P<head>\w)\B"),
+            ("Entity", r"^\w+"),
+        ],
+        vec![],
+        true,
+    );
+
+    let tokens = tfla.tokenize("ab c").unwrap();
+
+    assert_eq!(tokens[0].ty, "Head");
+    assert_eq!(tokens[0].group("head"), Some("a"));
+    assert_eq!(tokens[1].ty, "Entity");
+}
+
+fn literals() -> TFLA<'static> {
+    let mut tfla = TFLA::new(
+        vec![
+            ("SPACE", r"^\s"),
+            ("Float", r"^\d+\.\d+"),
+            ("Int", r"^-?(0x[0-9a-f]+|\d+)"),
+            ("Bool", r"^(true|false)"),
+            ("String", r#"^"([^"\\]|\\.)*""#),
+            ("Char", r"^'.'"),
+        ],
+        vec![],
+        true,
+    );
+    tfla.add_converter("Float", Converter::Float);
+    tfla.add_converter("Int", Converter::Int);
+    tfla.add_converter("Bool", Converter::Bool);
+    tfla.add_converter("String", Converter::Str);
+    tfla.add_converter(
+        "Char",
+        Converter::custom(|c| Ok(TokenValue::Int(c.chars().nth(1).unwrap() as i64))),
+    );
+
+    tfla
+}
+
+#[test]
+fn token_values() {
+    let mut tfla = literals();
+    let tokens = tfla
+        .tokenize(r#"42 -0xff 1.5 true "a\n\"b\" \u{e7}" 'A'"#)
+        .unwrap();
+
+    let values: Vec<TokenValue> = tokens.into_iter().map(|t| t.value.unwrap()).collect();
+
+    assert_eq!(
+        values,
+        vec![
+            TokenValue::Int(42),
+            TokenValue::Int(-255),
+            TokenValue::Float(1.5),
+            TokenValue::Bool(true),
+            TokenValue::Str("a\n\"b\" ç".to_string()),
+            TokenValue::Int(65)
+        ]
+    );
+}
+
+#[test]
+fn invalid_values_are_lexing_errors() {
+    let mut tfla = literals();
+    let error = tfla.tokenize("1 99999999999999999999").unwrap_err();
+
+    assert!(matches!(error.kind, TokenizeErrorKind::InvalidValue(_)));
+    assert_eq!((error.line, error.column), (1, 3));
+
+    tfla.recover_errors = true;
+    let tokens = tfla.tokenize(r#""\q" 2"#).unwrap();
+
+    assert_eq!(kinds(&tokens), vec![("ERROR", r#""\q""#), ("Int", "2")]);
+    assert_eq!(tfla.errors.len(), 1);
+}
+
+#[test]
+fn int_converter_takes_one_sign() {
+    assert_eq!(Converter::Int.convert("-0x1_0"), Ok(TokenValue::Int(-16)));
+    assert_eq!(Converter::Int.convert("+5"), Ok(TokenValue::Int(5)));
+
+    for content in ["+-5", "-+5", "++5", "--5", "0x-5", "-0x+5", "0b+1"] {
+        assert!(Converter::Int.convert(content).is_err(), "{}", content);
+    }
+}
+
+#[test]
+fn keywords_retag_identifiers() {
+    let mut tfla = TFLA::new(vec![("SPACE", r"^\s"), ("Entity", r"^\w+")], vec![], true);
+    tfla.add_keywords("Entity", &["if", "else", "while"]);
+    tfla.add_converter("Entity", Converter::Str);
+
+    let tokens = tfla.tokenize("if iff else x while").unwrap();
+
+    assert_eq!(
+        kinds(&tokens),
+        vec![
+            ("if", "if"),
+            ("Entity", "iff"),
+            ("else", "else"),
+            ("Entity", "x"),
+            ("while", "while")
+        ]
+    );
+    assert_eq!(tokens[0].value, None);
+    assert_eq!(tokens[1].value, Some(TokenValue::Str("iff".to_string())));
+}
+
+fn offside() -> TFLA<'static> {
+    let mut tfla = TFLA::new(
+        vec![
+            ("NEW_LINE", r"^(\r)?\n"),
+            ("SPACE", r"^[ \t]"),
+            ("Entity", r"^\w+"),
+        ],
+        vec![],
+        true,
+    );
+    tfla.add_comment(Comment::line(LINE_COMMENT, "#")).unwrap();
+    tfla.offside = Some(Offside::new(4));
+
+    tfla
+}
+
+#[test]
+fn offside_rule() {
+    let mut tfla = offside();
+    let tokens = tfla
+        .tokenize("a\n  b\n\n      # c\n  c\n\t  d\n  e\nf\n  g")
+        .unwrap();
+
+    let types: Vec<&str> = tokens
+        .iter()
+        .map(|t| t.ty)
+        .filter(|ty| *ty != "NEW_LINE")
+        .collect();
+
+    assert_eq!(
+        types,
+        vec![
+            "Entity", "INDENT", "Entity", "Entity", "INDENT", "Entity", "DEDENT", "Entity",
+            "DEDENT", "Entity", "INDENT", "Entity", "DEDENT"
+        ]
+    );
+    assert_eq!(
+        (tokens[2].line, tokens[2].start, tokens[2].content),
+        (2, 1, "")
+    );
+}
+
+#[test]
+fn inconsistent_indentation() {
+    let mut tfla = offside();
+    let error = tfla.tokenize("a\n    b\n  c").unwrap_err();
+
+    assert_eq!(error.kind, TokenizeErrorKind::InconsistentIndentation);
+    assert_eq!((error.line, error.column, error.offset), (3, 1, 8));
+
+    tfla.recover_errors = true;
+    let tokens = tfla.tokenize("a\n    b\n  c\nd").unwrap();
+    let types: Vec<&str> = tokens.iter().map(|t| t.ty).collect();
+
+    assert_eq!(
+        types,
+        vec![
+            "Entity", "NEW_LINE", "INDENT", "Entity", "NEW_LINE", "DEDENT", "ERROR", "Entity",
+            "NEW_LINE", "DEDENT", "Entity"
+        ]
+    );
+    assert_eq!(tfla.errors.len(), 1);
+}
+
+#[test]
+fn token_kinds_and_spans() {
+    let mut tfla = commented();
+    tfla.keep_comments = true;
+    tfla.add_keywords("Entity", &["if"]);
+
+    let entity = tfla.kind("Entity").unwrap();
+    let keyword = tfla.kind("if").unwrap();
+    assert_ne!(entity, keyword);
+    assert_eq!(tfla.kind_name(keyword), Some("if"));
+    assert_eq!(tfla.kind("Missing"), None);
+
+    let tokens = tfla.tokenize("if ção // c").unwrap();
+    let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+
+    assert_eq!(
+        kinds,
+        vec![keyword, entity, tfla.kind(LINE_COMMENT).unwrap()]
+    );
+
+    let span = tokens[1].span();
+    assert_eq!((span.start, span.end, span.line, span.column), (3, 8, 1, 4));
+    assert_eq!(span.range(), tokens[1].range());
+
+    let owned: Vec<OwnedToken> =
+        std::thread::spawn(move || tokens.into_iter().map(OwnedToken::from).collect())
+            .join()
+            .unwrap();
+
+    assert_eq!(owned[1].kind, entity);
+    assert_eq!(owned[1].span(), span);
+}
+
+#[test]
+fn reserved_searcher_names() {
+    for name in ["ERROR", "INDENT", "DEDENT"] {
+        match TFLA::try_new(vec![("SPACE", r"^\s"), (name, r"^\w+")], vec![], true) {
+            Err((found, SearcherError::Reserved)) => assert_eq!(found, name),
+            _ => panic!("[{}] is reserved", name),
+        }
+    }
+
+    assert!(matches!(
+        TFLA::try_new(vec![("Entity", r"^(")], vec![], true),
+        Err(("Entity", SearcherError::Regex(_)))
+    ));
+
+    let exaust = Exaust::from_source("se ERROR ^\\w+".to_string());
+    assert!(matches!(
+        TFLA::from_exaust(&exaust, true),
+        Err(ExaustError::Reserved { .. })
+    ));
+}